@@ -1,22 +1,39 @@
-use log::debug;
-use reqwest::header;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{debug, warn};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{json, Value};
 
 use crate::{
-    constants::{AUTH_FILENAME, DEMO_TRADING_URL, LIVE_MARKET_DATA_URL, LIVE_TRADING_URL, MARKET_REPLAY_WS},
+    config::ServerUrls,
     error::Error,
     models::{
         access_token::AccessTokenInfo,
         contract::{Contract, Maturity},
-        product::Product, position::Position, orders::OrderTicket, account::Balances,
+        product::Product, position::Position, account::Balances,
+        orders::{CancelOrder, CommandResponse, CommandResult, LiquidatePosition, ModifyOrder, OcoOrder, OcoResult, OrderTicket, OsoOrder, OsoResult, PlaceOrderResult},
+        execution::{Command, CommandReport, ExecutionReport, Fill, Order, OrderVersion},
+        order_strategy::{InterruptOrderStrategy, ModifyOrderStrategy, OrderStrategyResult, OrderStrategyStatusResult, StartOrderStrategy},
     },
-    rest::endpoints::{Endpoint, CONTRACT_DEPS, CONTRACT_FIND, CONTRACT_MATURITY, PRODUCTS_LIST, LIST_POSITIONS, PLACE_ORDER, ACCOUNTS_LIST, CASH_BALANCE_LIST},
-    utils::delete_file,
+    client_builder::TradovateClientBuilder,
+    rest::{rate_limit::RateLimiter, retry::{RequestOptions, RetryPolicy}},
+    rest::endpoints::{Endpoint, CONTRACT_DEPS, CONTRACT_FIND, CONTRACT_MATURITY, PRODUCTS_LIST, LIST_POSITIONS, PLACE_ORDER, ACCOUNTS_LIST, CASH_BALANCE_LIST, ACCESS_TOKEN_RENEW, MODIFY_ORDER, CANCEL_ORDER, LIQUIDATE_POSITION, PLACE_OSO, PLACE_OCO, START_ORDER_STRATEGY, MODIFY_ORDER_STRATEGY, INTERRUPT_ORDER_STRATEGY, ORDER_LIST, ORDER_ITEM, ORDER_VERSION_DEPS, FILL_LIST, FILL_DEPS, EXECUTION_REPORT_DEPS, COMMAND_DEPS, COMMAND_REPORT_DEPS},
+    token_manager::TokenHandle,
+    token_store::{TokenKey, TokenStore},
 };
 
-#[derive(Debug, Clone)]
+/// How many penalty tickets a request waits out before the penalty is returned as an error.
+pub const MAX_PENALTY_RETRIES: u32 = 3;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Server {
+    #[serde(alias = "Live")]
     Live,
+    #[default]
+    #[serde(alias = "Demo")]
     Demo,
 }
 #[derive(Debug, Clone, Copy)]
@@ -47,14 +64,26 @@ pub struct TradovateClient {
     pub password: String,
     pub app_id: String,
     pub app_version: String,
-    cid: i64,
-    secret: String,
-    pub access_token_info: Option<AccessTokenInfo>,
+    pub(crate) cid: i64,
+    pub(crate) secret: String,
+    /// Shared between every clone of the client, see `TokenManager` for automatic renewal.
+    pub token: TokenHandle,
     pub http_client: reqwest::Client,
+    pub urls: ServerUrls,
+    pub device_id: String,
+    /// Where the token is saved between runs, shared between every clone of the client.
+    pub token_store: Arc<dyn TokenStore>,
+    /// Spreads requests out per endpoint class, shared between every clone of the client.
+    pub rate_limiter: RateLimiter,
+    pub(crate) request_options: RequestOptions,
     pub account_id: Option<i64>,
 }
 impl TradovateClient {
     /// The `TradovateClient` struct contains all the necessary information to make requests to Tradovate's api.
+    ///
+    /// # Panics
+    /// If the http client can't be built, `TradovateClient::builder` returns the error instead.
+    #[deprecated(note = "use `TradovateClient::builder`, which also reads credentials from the environment or a config file")]
     pub fn new(
         server_type: Server,
         app_id: &str,
@@ -64,54 +93,24 @@ impl TradovateClient {
         username: String,
         password: String,
     ) -> Self {
-        let mut default_headers = header::HeaderMap::new();
-        default_headers.insert("Content-Type", "application/json".parse().unwrap());
-        default_headers.insert("Accept", "application/json".parse().unwrap());
-        let client = reqwest::ClientBuilder::new()
-            .default_headers(default_headers)
+        Self::builder(server_type)
+            .app(app_id, app_version)
+            .api_key(cid, &secret)
+            .user(&username, &password)
             .build()
-            .unwrap();
-        Self {
-            app_id: app_id.to_string(),
-            app_version: app_version.to_string(),
-            access_token_info: None,
-            server_type,
-            cid,
-            secret,
-            username,
-            password,
-            http_client: client,
-            account_id: None,
-        }
+            .expect("failed to build the http client")
+    }
+    pub fn builder(server_type: Server) -> TradovateClientBuilder {
+        TradovateClientBuilder::new(server_type)
     }
-    /// This function will load the necessary values from the user's environment variables.
+    /// Builds a client with the credentials from the environment, see `Credentials::from_env`.
     /// Can be used with .env files.
-    /// Make sure the envs are set before calling this function.
-    /// `TRADOVATE_APP_ID` - The app's name you selected when creating API keys
-    /// `TRADOVATE_APP_VERSION` - The app version provided by Tradovate
-    /// `TRADOVATE_USERNAME` - Your tradovate username
-    /// `TRADOVATE_PASSWORD` - Your tradovate password
-    /// `TRADOVATE_CID` - The cid provided by Tradovate
-    /// `TRADOVATE_SECRET` - The secret provided by Tradovate
-    pub fn load_from_env(server_type: Server) -> Self {
-        dotenv::dotenv().ok();
-        let app_id = std::env::var("TRADOVATE_APP_ID").expect("TRADOVATE_APP_ID must be set");
-        let app_version =
-            std::env::var("TRADOVATE_APP_VERSION").expect("TRADOVATE_APP_VERSION must be set");
-        let cid = std::env::var("TRADOVATE_CID").expect("TRADOVATE_CID must be set");
-        let cid = cid.parse::<i64>().unwrap();
-        let secret = std::env::var("TRADOVATE_SECRET").expect("TRADOVATE_SECRET must be set");
-        let username = std::env::var("TRADOVATE_USERNAME").expect("TRADOVATE_USERNAME must be set");
-        let password = std::env::var("TRADOVATE_PASSWORD").expect("TRADOVATE_PASSWORD must be set");
-        Self::new(
-            server_type,
-            &app_id,
-            &app_version,
-            cid,
-            secret,
-            username,
-            password,
-        )
+    pub fn load_from_env(server_type: Server) -> Result<Self, Error> {
+        TradovateClientBuilder::from_env(server_type)?.build()
+    }
+    /// Builds a client from a TOML or YAML config file, see `ClientConfig`.
+    pub fn load_from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        TradovateClientBuilder::from_file(path)?.build()
     }
     fn get_auth_data(&self) -> Value {
         json!({
@@ -121,184 +120,305 @@ impl TradovateClient {
             "appVersion": self.app_version,
             "cid":        self.cid,
             "sec":        self.secret,
-            "deviceId":   machine_uid::get().unwrap_or("buster-linux-docker".to_string())
+            "deviceId":   self.device_id
         })
     }
     pub fn url(&self, resource_type: ResourceType, protocol: Protocol) -> String {
-        match self.server_type {
-            Server::Live => match resource_type {
-                ResourceType::Trading => protocol.add_prefix(LIVE_TRADING_URL),
-                ResourceType::MarketData => protocol.add_prefix(LIVE_MARKET_DATA_URL),
-                ResourceType::MarketReplay => protocol.add_prefix(MARKET_REPLAY_WS),
-            },
-            Server::Demo => match resource_type {
-                ResourceType::Trading => protocol.add_prefix(DEMO_TRADING_URL),
-                ResourceType::MarketData => protocol.add_prefix(LIVE_MARKET_DATA_URL),
-                ResourceType::MarketReplay => protocol.add_prefix(MARKET_REPLAY_WS),
-            },
-        }
+        self.urls.url(resource_type, protocol)
     }
+    /// Sends the request once the endpoint's rate limit allows it.
+    /// A penalty response is waited out and the request sent again with its `p-ticket`,
+    /// up to `MAX_PENALTY_RETRIES` times. Transient failures of idempotent requests are
+    /// retried as the client's `RetryPolicy` allows.
     async fn call_endpoint(
         &self,
         endpoint: Endpoint,
-        params: Option<Value>,
-        request_body: Option<Value>,
-    ) -> Result<String, reqwest::Error> {
-        let url = format!(
-            "{}{}",
-            self.url(ResourceType::Trading, Protocol::Https),
-            endpoint.path
-        );
-        let mut request = self.http_client.request(endpoint.method, url);
+        mut params: Option<Value>,
+        mut request_body: Option<Value>,
+    ) -> Result<String, Error> {
+        let retry_policy = self.request_options.retry_policy;
+        let mut penalties = 0;
+        let mut attempt = 0;
+        loop {
+            self.rate_limiter.acquire(&endpoint).await;
+            match self.send_request(&endpoint, &params, &request_body).await {
+                Err(Error::RateLimited { ticket: Some(ticket), wait }) if penalties < MAX_PENALTY_RETRIES => {
+                    penalties += 1;
+                    let wait = wait.unwrap_or(Duration::from_secs(1));
+                    warn!("{} returned a penalty ticket, retrying in {:?}", endpoint.path, wait);
+                    tokio::time::sleep(wait).await;
+                    attach_penalty_ticket(&mut params, &mut request_body, &ticket);
+                }
+                Err(e) if retry_policy.should_retry(attempt, &endpoint, request_body.as_ref(), &e) => {
+                    let wait = match &e {
+                        Error::RateLimited { wait: Some(wait), .. } => *wait,
+                        _ => retry_policy.jittered_backoff(attempt),
+                    };
+                    attempt += 1;
+                    warn!("{} failed with {}, retry {} in {:?}", endpoint.path, e, attempt, wait);
+                    tokio::time::sleep(wait).await;
+                }
+                result => return result,
+            }
+        }
+    }
+    async fn send_request(
+        &self,
+        endpoint: &Endpoint,
+        params: &Option<Value>,
+        request_body: &Option<Value>,
+    ) -> Result<String, Error> {
+        let url = format!("{}{}", self.url(ResourceType::Trading, Protocol::Https), endpoint.path);
+        let mut request = self
+            .http_client
+            .request(endpoint.method.clone(), url)
+            .timeout(self.request_options.timeout);
         if let Some(params) = params {
-            request = request.query(&params);
+            request = request.query(params);
         }
         if let Some(request_body) = request_body {
-            request = request.json(&request_body);
+            request = request.json(request_body);
         }
-        if let Some(access_token_info) = &self.access_token_info {
-            request = request.bearer_auth(&access_token_info.access_token);
+        // market data only logins renew their market data token, every other call needs the trading token
+        let access_token = if endpoint.path == ACCESS_TOKEN_RENEW.path {
+            self.token.renewal_token()
+        } else {
+            self.token.access_token()
+        };
+        if let Some(access_token) = access_token {
+            request = request.bearer_auth(access_token);
         }
         let response = request.send().await?;
         debug!("Response: {:?}", response);
-        Ok(response.text().await?)
+        let status = response.status();
+        let body = response.text().await?;
+        check_response(status, body)
     }
-    pub fn ws_auth_msg(&self) -> String {
-        format!("authorize\n0\n\n{}", self.access_token_info.as_ref().unwrap().access_token)
+    /// A clone of the client, sharing its token and rate limits, whose calls are retried with `retry_policy`.
+    /// `client.with_retry_policy(RetryPolicy::none()).get_orders()` makes a single attempt.
+    pub fn with_retry_policy(&self, retry_policy: RetryPolicy) -> Self {
+        let mut client = self.clone();
+        client.request_options.retry_policy = retry_policy;
+        client
     }
-    async fn get_access_token(&mut self) -> Result<(), Error> {
+    /// A clone of the client, sharing its token and rate limits, whose calls time out after `timeout`.
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        let mut client = self.clone();
+        client.request_options.timeout = timeout;
+        client
+    }
+    pub fn request_options(&self) -> RequestOptions {
+        self.request_options
+    }
+    async fn call_endpoint_as<T: DeserializeOwned>(
+        &self,
+        endpoint: Endpoint,
+        params: Option<Value>,
+        request_body: Option<Value>,
+    ) -> Result<T, Error> {
+        let response = self.call_endpoint(endpoint, params, request_body).await?;
+        debug!("{}", response);
+        Ok(serde_json::from_str::<T>(&response)?)
+    }
+    /// Sends an order command, a refused one is returned as `Error::OrderRejected` like `OrderGateway` does.
+    async fn call_command<T: DeserializeOwned + CommandResponse>(&self, endpoint: Endpoint, request_body: Value) -> Result<T, Error> {
+        self.call_endpoint_as::<T>(endpoint, None, Some(request_body)).await?.check()
+    }
+    /// The message authorizing a socket of `resource_type` with the current token.
+    pub fn ws_auth_msg(&self, resource_type: ResourceType, request_id: usize) -> Result<String, Error> {
+        self.current_token()
+            .map(|access_token_info| access_token_info.ws_auth_msg(resource_type, request_id))
+    }
+    fn current_token(&self) -> Result<AccessTokenInfo, Error> {
+        self.token
+            .current()
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))
+    }
+    /// True if the login has access to the live server.
+    pub fn has_live(&self) -> bool {
+        self.token.current().is_some_and(|t| t.has_live)
+    }
+    /// True if the login has a funded account.
+    pub fn has_funded(&self) -> bool {
+        self.token.current().is_some_and(|t| t.has_funded)
+    }
+    pub fn has_market_data(&self) -> bool {
+        self.token.current().is_some_and(|t| t.has_market_data)
+    }
+    /// Fails unless the current login can trade on the client's server, see `AccessTokenInfo::check_trading`.
+    pub fn check_trading(&self) -> Result<(), Error> {
+        self.current_token()?.check_trading(self.server_type)
+    }
+    async fn get_access_token(&self) -> Result<(), Error> {
         use crate::rest::endpoints::ACCESS_TOKEN;
-        match self
-            .call_endpoint(ACCESS_TOKEN, None, Some(self.get_auth_data()))
+        let access_token_info = match self
+            .call_endpoint_as::<AccessTokenInfo>(ACCESS_TOKEN, None, Some(self.get_auth_data()))
             .await
         {
-            Ok(access_token_info) => {
-                match serde_json::from_str::<AccessTokenInfo>(&access_token_info) {
-                    Ok(access_token_info) => {
-                        self.access_token_info = Some(access_token_info);
-                        Ok(())
-                    }
-                    Err(e) => Err(Error::Json(e)),
-                }
+            Err(Error::Api { message, .. }) => return Err(Error::Auth(message)),
+            result => result?,
+        };
+        self.token.set(access_token_info);
+        Ok(())
+    }
+    /// Requests a new token with the user's credentials and saves it to the token store.
+    pub async fn login(&self) -> Result<(), Error> {
+        self.get_access_token().await?;
+        self.save_token().await;
+        Ok(())
+    }
+    /// Exchanges the current, still valid, token for a new one without sending the credentials again.
+    /// The new token is saved to the token store and pushed to every open websocket.
+    /// Market data only logins are renewed with their market data token, see `TokenHandle::renewal_token`.
+    pub async fn renew_access_token(&self) -> Result<(), Error> {
+        if self.token.renewal_token().is_none() {
+            return Err(Error::Auth("Cannot renew without an access token".to_string()));
+        }
+        let access_token_info = self
+            .call_endpoint_as::<AccessTokenInfo>(ACCESS_TOKEN_RENEW, None, None)
+            .await?;
+        self.token.set(access_token_info);
+        self.save_token().await;
+        Ok(())
+    }
+    /// The key the client's token is saved under in its token store.
+    pub fn token_key(&self) -> TokenKey {
+        TokenKey::new(self.server_type, &self.username)
+    }
+    /// Runs a call to the token store on the blocking thread pool, as the file store waits on a file lock.
+    async fn with_token_store<T: Send + 'static>(
+        &self,
+        call: impl FnOnce(&dyn TokenStore, &TokenKey) -> Result<T, Error> + Send + 'static,
+    ) -> Result<T, Error> {
+        let token_store = self.token_store.clone();
+        let key = self.token_key();
+        tokio::task::spawn_blocking(move || call(token_store.as_ref(), &key))
+            .await
+            .map_err(|e| Error::Other(format!("token store task failed: {}", e)))?
+    }
+    /// A token that could not be saved only costs a login on the next run, so the error is logged and not returned.
+    async fn save_token(&self) {
+        if let Some(access_token_info) = self.token.current() {
+            let saved = self
+                .with_token_store(move |token_store, key| token_store.save(key, &access_token_info))
+                .await;
+            if let Err(e) = saved {
+                warn!("Could not save the access token: {}", e);
             }
-            Err(e) => Err(Error::Reqwest(e)),
         }
     }
-    /// This function will return a new instance of the client with an access token.
-    /// It will either load the token from the auth file, check that its still valid, or
+    /// This function will give the client an access token.
+    /// It will either load the token from the token store, check that its still valid, or
     /// request a new one.
-    pub async fn authenticate(&mut self) -> Result<(), Error> {
-        match crate::utils::open_json(AUTH_FILENAME) {
-            Ok(access_token_info) => {
-                match serde_json::from_value::<AccessTokenInfo>(access_token_info) {
-                    Ok(access_token_info) => {
-                        if access_token_info.is_expired() {
-                            delete_file(AUTH_FILENAME);
-                            self.get_access_token().await?;
-                            crate::utils::create_json_file(
-                                AUTH_FILENAME,
-                                &self.access_token_info,
-                            );
-                            Ok(())
-                        } else {
-                            self.access_token_info = Some(access_token_info);
-                            Ok(())
-                        }
-                    }
-                    Err(e) => Err(Error::Json(e)),
+    pub async fn authenticate(&self) -> Result<(), Error> {
+        match self.with_token_store(|token_store, key| token_store.load(key)).await {
+            Ok(Some(access_token_info)) if !access_token_info.is_expired() => {
+                self.token.set(access_token_info);
+                Ok(())
+            }
+            Ok(Some(_)) => {
+                if let Err(e) = self.with_token_store(|token_store, key| token_store.clear(key)).await {
+                    warn!("Could not clear the expired access token: {}", e);
                 }
+                self.login().await
             }
-            Err(_) => {
-                self.get_access_token().await?;
-                crate::utils::create_json_file(AUTH_FILENAME, &self.access_token_info);
-                Ok(())
+            Ok(None) => self.login().await,
+            Err(e) => {
+                warn!("Could not load the saved access token: {}", e);
+                self.login().await
             }
         }
     }
     pub async fn get_contract_deps(&self) -> Result<String, Error> {
-        match self.call_endpoint(CONTRACT_DEPS, None, None).await {
-            Ok(contract_deps) => Ok(contract_deps),
-            Err(e) => Err(Error::Reqwest(e)),
-        }
+        self.call_endpoint(CONTRACT_DEPS, None, None).await
     }
     pub async fn get_products_list(&self) -> Result<Vec<Product>, Error> {
-        match self.call_endpoint(PRODUCTS_LIST, None, None).await {
-            Ok(products_list) => match serde_json::from_str::<Vec<Product>>(&products_list) {
-                Ok(products_list) => Ok(products_list),
-                Err(e) => Err(Error::Json(e)),
-            },
-            Err(e) => Err(Error::Reqwest(e)),
-        }
+        self.call_endpoint_as(PRODUCTS_LIST, None, None).await
     }
     pub async fn find_contract(&self, name: &str) -> Result<Contract, Error> {
         let params = json!({ "name": name });
-        match self.call_endpoint(CONTRACT_FIND, Some(params), None).await {
-            Ok(contract_str) => match serde_json::from_str::<Contract>(&contract_str) {
-                Ok(contract) => Ok(contract),
-                Err(e) => Err(Error::Json(e)),
-            },
-            Err(e) => Err(Error::Reqwest(e)),
-        }
+        self.call_endpoint_as(CONTRACT_FIND, Some(params), None).await
     }
     pub async fn find_maturity(&self, id: i64) -> Result<Maturity, Error> {
         let params = json!({ "id": id });
-        match self
-            .call_endpoint(CONTRACT_MATURITY, Some(params), None)
-            .await
-        {
-            Ok(contract_mat) => match serde_json::from_str::<Maturity>(&contract_mat) {
-                Ok(mat) => Ok(mat),
-                Err(e) => Err(Error::Json(e)),
-            },
-            Err(e) => Err(Error::Reqwest(e)),
-        }
+        self.call_endpoint_as(CONTRACT_MATURITY, Some(params), None).await
     }
     pub async fn get_positions(&self) -> Result<Vec<Position>,Error> {
-        match self.call_endpoint(LIST_POSITIONS, None, None).await {
-            Ok(positions) => {
-                match serde_json::from_str::<Vec<Position>>(&positions) {
-                    Ok(positions) => Ok(positions),
-                    Err(e) => Err(Error::Json(e)),
-                }
-            },
-            Err(e) => Err(Error::Reqwest(e)),
-        }
+        self.call_endpoint_as(LIST_POSITIONS, None, None).await
     }
-    pub async fn place_order(&self,order_ticket:OrderTicket) -> Result<Value,Error> {
+    /// The order is validated before sending, see `OrderTicket::validate`.
+    /// A refused order is returned as `Error::OrderRejected`.
+    pub async fn place_order(&self,order_ticket:OrderTicket) -> Result<PlaceOrderResult,Error> {
+        order_ticket.validate()?;
         let value = json!(order_ticket);
         debug!("{}",serde_json::to_string_pretty(&value).unwrap());
-        match self.call_endpoint(PLACE_ORDER, None, Some(value)).await {
-            Ok(order) => {
-                debug!("{}",order);
-                match serde_json::from_str::<Value>(&order) {
-                    Ok(order) => Ok(order),
-                    Err(e) => Err(Error::Json(e)),
-                }
-            },
-            Err(e) => Err(Error::Reqwest(e)),
-        }
+        self.call_command(PLACE_ORDER, value).await
+    }
+    /// Places an entry with one or two brackets that are only sent once it fills.
+    /// The brackets are validated before sending, see `OsoOrder::validate`.
+    pub async fn place_oso(&self,oso_order:OsoOrder) -> Result<OsoResult,Error> {
+        oso_order.validate()?;
+        self.call_command(PLACE_OSO, json!(oso_order)).await
+    }
+    /// Places two orders where the fill of one cancels the other, validated with `OcoOrder::validate`.
+    pub async fn place_oco(&self,oco_order:OcoOrder) -> Result<OcoResult,Error> {
+        oco_order.validate()?;
+        self.call_command(PLACE_OCO, json!(oco_order)).await
+    }
+    pub async fn modify_order(&self,modify_order:ModifyOrder) -> Result<CommandResult,Error> {
+        modify_order.validate()?;
+        self.call_command(MODIFY_ORDER, json!(modify_order)).await
+    }
+    pub async fn cancel_order(&self,cancel_order:CancelOrder) -> Result<CommandResult,Error> {
+        self.call_command(CANCEL_ORDER, json!(cancel_order)).await
+    }
+    /// Cancels the working orders of the contract and flattens its position with a market order.
+    pub async fn liquidate_position(&self,liquidate_position:LiquidatePosition) -> Result<PlaceOrderResult,Error> {
+        self.call_command(LIQUIDATE_POSITION, json!(liquidate_position)).await
+    }
+    /// Starts a server side strategy, the server places the entry and manages the brackets from then on.
+    pub async fn start_order_strategy(&self,strategy:StartOrderStrategy) -> Result<OrderStrategyResult,Error> {
+        self.call_command(START_ORDER_STRATEGY, json!(strategy)).await
+    }
+    pub async fn modify_order_strategy(&self,modify_strategy:ModifyOrderStrategy) -> Result<OrderStrategyStatusResult,Error> {
+        self.call_command(MODIFY_ORDER_STRATEGY, json!(modify_strategy)).await
+    }
+    /// Stops the strategy, the orders it already placed are left as they are.
+    pub async fn interrupt_order_strategy(&self,order_strategy_id:i64) -> Result<OrderStrategyStatusResult,Error> {
+        let body = InterruptOrderStrategy { order_strategy_id };
+        self.call_command(INTERRUPT_ORDER_STRATEGY, json!(body)).await
+    }
+    pub async fn get_orders(&self) -> Result<Vec<Order>,Error> {
+        self.call_endpoint_as(ORDER_LIST, None, None).await
+    }
+    pub async fn get_order(&self,order_id:i64) -> Result<Order,Error> {
+        self.call_endpoint_as(ORDER_ITEM, Some(json!({ "id": order_id })), None).await
+    }
+    /// Every version of the order, one per modification.
+    pub async fn get_order_versions(&self,order_id:i64) -> Result<Vec<OrderVersion>,Error> {
+        self.call_endpoint_as(ORDER_VERSION_DEPS, Some(json!({ "masterid": order_id })), None).await
+    }
+    pub async fn get_fills(&self) -> Result<Vec<Fill>,Error> {
+        self.call_endpoint_as(FILL_LIST, None, None).await
+    }
+    pub async fn get_order_fills(&self,order_id:i64) -> Result<Vec<Fill>,Error> {
+        self.call_endpoint_as(FILL_DEPS, Some(json!({ "masterid": order_id })), None).await
+    }
+    pub async fn get_execution_reports(&self,order_id:i64) -> Result<Vec<ExecutionReport>,Error> {
+        self.call_endpoint_as(EXECUTION_REPORT_DEPS, Some(json!({ "masterid": order_id })), None).await
+    }
+    /// The place, modify and cancel commands sent for the order.
+    pub async fn get_commands(&self,order_id:i64) -> Result<Vec<Command>,Error> {
+        self.call_endpoint_as(COMMAND_DEPS, Some(json!({ "masterid": order_id })), None).await
+    }
+    pub async fn get_command_reports(&self,command_id:i64) -> Result<Vec<CommandReport>,Error> {
+        self.call_endpoint_as(COMMAND_REPORT_DEPS, Some(json!({ "masterid": command_id })), None).await
     }
     pub async fn get_accounts_list(&self) -> Result<Value,Error> {
-        match self.call_endpoint(ACCOUNTS_LIST, None, None).await {
-            Ok(order) => {
-                match serde_json::from_str::<Value>(&order) {
-                    Ok(order) => Ok(order),
-                    Err(e) => Err(Error::Json(e)),
-                }
-            },
-            Err(e) => Err(Error::Reqwest(e)),
-        }
+        self.call_endpoint_as(ACCOUNTS_LIST, None, None).await
     }
     pub async fn get_cash_balances(&self) -> Result<Balances,Error> {
-        match self.call_endpoint(CASH_BALANCE_LIST, None, None).await {
-            Ok(order) => {
-                match serde_json::from_str::<Balances>(&order) {
-                    Ok(order) => Ok(order),
-                    Err(e) => Err(Error::Json(e)),
-                }
-            },
-            Err(e) => Err(Error::Reqwest(e)),
-        }
+        self.call_endpoint_as(CASH_BALANCE_LIST, None, None).await
     }
     /// This function will get the account id from the cash balances and add it to the client
     /// so that it can be used in other calls.
@@ -312,3 +432,56 @@ impl TradovateClient {
         }
     }
 }
+
+/// Turns error statuses and the error bodies Tradovate sends with a 200 into errors.
+pub(crate) fn check_response(status: reqwest::StatusCode, body: String) -> Result<String, Error> {
+    let value = serde_json::from_str::<Value>(&body).unwrap_or(Value::Null);
+    if value["p-captcha"].as_bool() == Some(true) {
+        return Err(Error::CaptchaRequired {
+            ticket: value["p-ticket"].as_str().map(str::to_string),
+        });
+    }
+    if value.get("p-ticket").is_some() {
+        return Err(Error::RateLimited {
+            ticket: value["p-ticket"].as_str().map(str::to_string),
+            wait: value["p-time"].as_u64().map(std::time::Duration::from_secs),
+        });
+    }
+    let error_text = value
+        .get("errorText")
+        .and_then(Value::as_str)
+        .filter(|error_text| !error_text.is_empty())
+        .map(str::to_string);
+    if status.is_success() {
+        return match error_text {
+            Some(message) => Err(Error::Api {
+                status: status.as_u16(),
+                message,
+            }),
+            None => Ok(body),
+        };
+    }
+    let message = error_text.unwrap_or(body);
+    match status {
+        reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => Err(Error::Auth(message)),
+        reqwest::StatusCode::TOO_MANY_REQUESTS => Err(Error::RateLimited {
+            ticket: None,
+            wait: None,
+        }),
+        status => Err(Error::Api {
+            status: status.as_u16(),
+            message,
+        }),
+    }
+}
+
+/// Adds the penalty ticket to the body of the retried request, or to its query when it has no body.
+pub(crate) fn attach_penalty_ticket(params: &mut Option<Value>, request_body: &mut Option<Value>, ticket: &str) {
+    let target = if request_body.is_some() { request_body } else { params };
+    match target {
+        Some(Value::Object(fields)) => {
+            fields.insert("p-ticket".to_string(), json!(ticket));
+        }
+        _ => *target = Some(json!({ "p-ticket": ticket })),
+    }
+}
@@ -0,0 +1,180 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::header;
+
+use crate::{
+    client::{Server, TradovateClient},
+    config::{ClientConfig, Credentials, ServerUrls},
+    error::Error,
+    rest::{
+        rate_limit::{RateLimiter, RateLimits},
+        retry::{RequestOptions, RetryPolicy},
+    },
+    token_manager::TokenHandle,
+    token_store::{FileTokenStore, TokenStore},
+};
+
+/// Builds a `TradovateClient`, every setting but the credentials has a default.
+/// The credentials can be set one by one, or all at once from the environment or a config file.
+#[derive(Debug, Clone)]
+pub struct TradovateClientBuilder {
+    server_type: Server,
+    app_id: Option<String>,
+    app_version: Option<String>,
+    cid: Option<i64>,
+    secret: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    urls: Option<ServerUrls>,
+    http_client: Option<reqwest::Client>,
+    device_id: Option<String>,
+    token_store: Option<Arc<dyn TokenStore>>,
+    connect_timeout: Duration,
+    request_options: RequestOptions,
+    rate_limits: RateLimits,
+}
+impl TradovateClientBuilder {
+    pub fn new(server_type: Server) -> Self {
+        Self {
+            server_type,
+            app_id: None,
+            app_version: None,
+            cid: None,
+            secret: None,
+            username: None,
+            password: None,
+            urls: None,
+            http_client: None,
+            device_id: None,
+            token_store: None,
+            connect_timeout: Duration::from_secs(10),
+            request_options: RequestOptions::default(),
+            rate_limits: RateLimits::default(),
+        }
+    }
+    /// A builder with the credentials from the environment, see `Credentials::from_env`.
+    pub fn from_env(server_type: Server) -> Result<Self, Error> {
+        Ok(Self::new(server_type).credentials(Credentials::from_env()?))
+    }
+    /// A builder with every setting of the TOML or YAML file, see `ClientConfig`.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Ok(Self::from_config(ClientConfig::from_file(path)?))
+    }
+    pub fn from_config(config: ClientConfig) -> Self {
+        let mut builder = Self::new(config.server).credentials(config.credentials);
+        builder.urls = config.urls;
+        builder.device_id = config.device_id;
+        if let Some(token_cache) = config.token_cache {
+            builder = builder.token_cache(token_cache);
+        }
+        builder
+    }
+    pub fn credentials(self, credentials: Credentials) -> Self {
+        self.app(&credentials.app_id, &credentials.app_version)
+            .api_key(credentials.cid, &credentials.secret)
+            .user(&credentials.username, &credentials.password)
+    }
+    /// The app's name selected when creating the API keys and its version.
+    pub fn app(mut self, app_id: &str, app_version: &str) -> Self {
+        self.app_id = Some(app_id.to_string());
+        self.app_version = Some(app_version.to_string());
+        self
+    }
+    /// The cid and secret provided by Tradovate.
+    pub fn api_key(mut self, cid: i64, secret: &str) -> Self {
+        self.cid = Some(cid);
+        self.secret = Some(secret.to_string());
+        self
+    }
+    pub fn user(mut self, username: &str, password: &str) -> Self {
+        self.username = Some(username.to_string());
+        self.password = Some(password.to_string());
+        self
+    }
+    /// Overrides the servers' urls, for example to point the client at a local stand-in server.
+    pub fn urls(mut self, urls: ServerUrls) -> Self {
+        self.urls = Some(urls);
+        self
+    }
+    /// Sends every request and opens every socket to `base_url`, see `ServerUrls::all`.
+    pub fn base_url(self, base_url: &str) -> Self {
+        self.urls(ServerUrls::all(base_url))
+    }
+    /// Uses `http_client` as it is, `connect_timeout` and the default json headers are not applied to it.
+    pub fn http_client(mut self, http_client: reqwest::Client) -> Self {
+        self.http_client = Some(http_client);
+        self
+    }
+    /// The device id sent with logins. Defaults to the machine's id.
+    pub fn device_id(mut self, device_id: &str) -> Self {
+        self.device_id = Some(device_id.to_string());
+        self
+    }
+    /// Where the token is saved between runs. Defaults to a `FileTokenStore` in the working directory.
+    pub fn token_store(mut self, token_store: impl TokenStore + 'static) -> Self {
+        self.token_store = Some(Arc::new(token_store));
+        self
+    }
+    /// Saves the token to a `FileTokenStore` in `directory`.
+    pub fn token_cache(self, directory: impl Into<PathBuf>) -> Self {
+        self.token_store(FileTokenStore::new(directory))
+    }
+    /// How long establishing a connection can take. Defaults to 10 seconds.
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+    /// How long a single attempt of a call can take, from sending to reading the body. Defaults to 30 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.request_options.timeout = timeout;
+        self
+    }
+    /// Defaults to three retries starting 250ms apart, see `RetryPolicy`.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.request_options.retry_policy = retry_policy;
+        self
+    }
+    pub fn rate_limits(mut self, rate_limits: RateLimits) -> Self {
+        self.rate_limits = rate_limits;
+        self
+    }
+    pub fn build(self) -> Result<TradovateClient, Error> {
+        let http_client = match self.http_client {
+            Some(http_client) => http_client,
+            None => {
+                let mut default_headers = header::HeaderMap::new();
+                default_headers.insert(header::CONTENT_TYPE, header::HeaderValue::from_static("application/json"));
+                default_headers.insert(header::ACCEPT, header::HeaderValue::from_static("application/json"));
+                reqwest::ClientBuilder::new()
+                    .default_headers(default_headers)
+                    .connect_timeout(self.connect_timeout)
+                    .build()?
+            }
+        };
+        Ok(TradovateClient {
+            app_id: required(self.app_id, "app id")?,
+            app_version: required(self.app_version, "app version")?,
+            cid: required(self.cid, "cid")?,
+            secret: required(self.secret, "secret")?,
+            username: required(self.username, "username")?,
+            password: required(self.password, "password")?,
+            token: TokenHandle::default(),
+            http_client,
+            urls: self.urls.unwrap_or_else(|| ServerUrls::for_server(&self.server_type)),
+            device_id: self
+                .device_id
+                .unwrap_or_else(|| machine_uid::get().unwrap_or("buster-linux-docker".to_string())),
+            token_store: self.token_store.unwrap_or_else(|| Arc::new(FileTokenStore::default())),
+            server_type: self.server_type,
+            rate_limiter: RateLimiter::new(self.rate_limits),
+            request_options: self.request_options,
+            account_id: None,
+        })
+    }
+}
+
+fn required<T>(value: Option<T>, name: &str) -> Result<T, Error> {
+    value.ok_or_else(|| Error::Config(format!("{} is not set", name)))
+}
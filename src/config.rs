@@ -0,0 +1,149 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::{Protocol, ResourceType, Server},
+    constants::{DEMO_TRADING_URL, LIVE_MARKET_DATA_URL, LIVE_TRADING_URL, MARKET_REPLAY_WS},
+    error::Error,
+};
+
+/// What the client logs in with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Credentials {
+    /// The app's name selected when creating the API keys.
+    pub app_id: String,
+    pub app_version: String,
+    pub cid: i64,
+    pub secret: String,
+    pub username: String,
+    pub password: String,
+}
+impl Credentials {
+    /// Reads the credentials from the environment, loading a `.env` file first if there is one.
+    /// `TRADOVATE_APP_ID` - The app's name you selected when creating API keys
+    /// `TRADOVATE_APP_VERSION` - The app version provided by Tradovate
+    /// `TRADOVATE_USERNAME` - Your tradovate username
+    /// `TRADOVATE_PASSWORD` - Your tradovate password
+    /// `TRADOVATE_CID` - The cid provided by Tradovate
+    /// `TRADOVATE_SECRET` - The secret provided by Tradovate
+    pub fn from_env() -> Result<Self, Error> {
+        dotenv::dotenv().ok();
+        let cid = env_var("TRADOVATE_CID")?;
+        Ok(Self {
+            app_id: env_var("TRADOVATE_APP_ID")?,
+            app_version: env_var("TRADOVATE_APP_VERSION")?,
+            cid: cid
+                .parse()
+                .map_err(|_| Error::Config(format!("TRADOVATE_CID must be a number, got {:?}", cid)))?,
+            secret: env_var("TRADOVATE_SECRET")?,
+            username: env_var("TRADOVATE_USERNAME")?,
+            password: env_var("TRADOVATE_PASSWORD")?,
+        })
+    }
+}
+
+fn env_var(name: &str) -> Result<String, Error> {
+    std::env::var(name).map_err(|_| Error::Config(format!("{} must be set", name)))
+}
+
+/// The base urls of the three servers the client talks to, such as `https://demo.tradovateapi.com`.
+/// The websocket urls are derived from them, so `http://127.0.0.1:8080` points every
+/// request and socket of that resource at a local server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerUrls {
+    pub trading: String,
+    pub market_data: String,
+    pub market_replay: String,
+}
+impl ServerUrls {
+    pub fn for_server(server_type: &Server) -> Self {
+        let trading = match server_type {
+            Server::Live => LIVE_TRADING_URL,
+            Server::Demo => DEMO_TRADING_URL,
+        };
+        Self {
+            trading: Protocol::Https.add_prefix(trading),
+            market_data: Protocol::Https.add_prefix(LIVE_MARKET_DATA_URL),
+            market_replay: Protocol::Https.add_prefix(MARKET_REPLAY_WS),
+        }
+    }
+    /// Every resource served from `base_url`.
+    pub fn all(base_url: &str) -> Self {
+        Self {
+            trading: base_url.to_string(),
+            market_data: base_url.to_string(),
+            market_replay: base_url.to_string(),
+        }
+    }
+    pub fn base_url(&self, resource_type: ResourceType) -> &str {
+        let base_url = match resource_type {
+            ResourceType::Trading => &self.trading,
+            ResourceType::MarketData => &self.market_data,
+            ResourceType::MarketReplay => &self.market_replay,
+        };
+        base_url.trim_end_matches('/')
+    }
+    pub fn url(&self, resource_type: ResourceType, protocol: Protocol) -> String {
+        let base_url = self.base_url(resource_type);
+        match protocol {
+            Protocol::Https => base_url.to_string(),
+            Protocol::Wss => {
+                let base_url = match base_url.split_once("://") {
+                    Some(("http", rest)) => format!("ws://{}", rest),
+                    Some((_, rest)) => format!("wss://{}", rest),
+                    None => format!("wss://{}", base_url),
+                };
+                format!("{}/v1/websocket", base_url)
+            }
+        }
+    }
+}
+
+/// A client configuration file, in TOML or YAML depending on its extension.
+/// Every field but the credentials is optional:
+/// ```toml
+/// server = "demo"
+/// app_id = "my app"
+/// app_version = "1.0"
+/// cid = 123
+/// secret = "..."
+/// username = "..."
+/// password = "..."
+/// device_id = "my-server"
+/// token_cache = "/var/lib/bot"
+///
+/// [urls]
+/// trading = "http://127.0.0.1:8080"
+/// market_data = "http://127.0.0.1:8080"
+/// market_replay = "http://127.0.0.1:8080"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientConfig {
+    #[serde(default)]
+    pub server: Server,
+    #[serde(flatten)]
+    pub credentials: Credentials,
+    pub urls: Option<ServerUrls>,
+    pub device_id: Option<String>,
+    /// The directory tokens are saved in, see `FileTokenStore`.
+    pub token_cache: Option<PathBuf>,
+}
+impl ClientConfig {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("could not read {}: {}", path.display(), e)))?;
+        let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+        match extension {
+            "toml" => toml::from_str(&contents).map_err(|e| Error::Config(format!("invalid {}: {}", path.display(), e))),
+            "yaml" | "yml" => {
+                serde_yaml::from_str(&contents).map_err(|e| Error::Config(format!("invalid {}: {}", path.display(), e)))
+            }
+            _ => Err(Error::Config(format!(
+                "{} must be a .toml, .yaml or .yml file",
+                path.display()
+            ))),
+        }
+    }
+}
@@ -5,6 +5,7 @@ pub const LIVE_MARKET_DATA_URL: &str = "md.tradovateapi.com";
 pub const DEMO_TRADING_URL: &str = "demo.tradovateapi.com";
 //pub const DEMO_MARKET_DATA_URL:&str = "md-demo.tradovateapi.com";
 // AUTH FILENAME
+#[deprecated(note = "tokens are saved per server and user by `FileTokenStore`, see `TokenKey::file_name`")]
 pub const AUTH_FILENAME: &str = "tradovate_auth.json";
 pub const MARKET_REPLAY_WS: &str = "replay.tradovateapi.com";
 
@@ -1,9 +1,154 @@
+use std::fmt;
+use std::time::Duration;
 
+use tokio_tungstenite::tungstenite;
+
+use crate::models::orders::OrderValidationError;
+use crate::models::shutdown::ShutdownMessage;
+
+/// Every error the crate returns, from the REST api and from the websockets alike.
 #[derive(Debug)]
 pub enum Error {
+    /// The credentials were refused, or there is no valid token to make the request with.
+    Auth(String),
+    /// Tradovate throttled the request, it can be retried after `wait` with the penalty `ticket`.
+    RateLimited { ticket: Option<String>, wait: Option<Duration> },
+    /// Tradovate wants a captcha solved before accepting more logins, which can only be done
+    /// by logging in once through its web or desktop app.
+    CaptchaRequired { ticket: Option<String> },
+    /// The login lacks the permission the action needs, such as trading on a market data only login.
+    NotPermitted(String),
+    /// Tradovate refused an order command, `reason` is its `failureReason`.
+    OrderRejected { reason: String, text: Option<String> },
+    /// The order was refused before being sent.
+    InvalidOrder(OrderValidationError),
+    /// The server answered with an error status, `message` is its `errorText` or the body.
+    Api { status: u16, message: String },
+    /// The request can't be sent as it is.
+    InvalidRequest(String),
+    /// The client is missing a setting or was given an invalid one.
+    Config(String),
+    /// The server sent something that does not follow the protocol.
+    Protocol(String),
+    /// The server closed the socket with a close frame.
+    ServerClosed { code: i64, reason: String },
+    /// The server shut the socket down.
+    Shutdown(ShutdownMessage),
+    /// No response arrived in time.
+    Timeout,
+    /// The socket closed, or none is connected.
+    ConnectionClosed,
     Reqwest(reqwest::Error),
+    WebSocket(Box<tungstenite::Error>),
     Json(serde_json::Error),
     Io(std::io::Error),
     Url(url::ParseError),
     Other(String),
-}
\ No newline at end of file
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Auth(message) => write!(f, "authentication failed: {}", message),
+            Error::RateLimited { wait, .. } => match wait {
+                Some(wait) => write!(f, "rate limited, retry in {:?}", wait),
+                None => write!(f, "rate limited"),
+            },
+            Error::CaptchaRequired { .. } => write!(
+                f,
+                "captcha required, log in through the Tradovate app before trying again"
+            ),
+            Error::NotPermitted(message) => write!(f, "not permitted: {}", message),
+            Error::OrderRejected { reason, text } => match text {
+                Some(text) => write!(f, "order rejected: {} {}", reason, text),
+                None => write!(f, "order rejected: {}", reason),
+            },
+            Error::InvalidOrder(e) => write!(f, "invalid order: {:?}", e),
+            Error::Api { status, message } => write!(f, "api error {}: {}", status, message),
+            Error::InvalidRequest(message) => write!(f, "invalid request: {}", message),
+            Error::Config(message) => write!(f, "invalid configuration: {}", message),
+            Error::Protocol(message) => write!(f, "protocol error: {}", message),
+            Error::Shutdown(shutdown) => write!(
+                f,
+                "server shut the socket down: {:?} {}",
+                shutdown.reason_code,
+                shutdown.reason.as_deref().unwrap_or_default()
+            ),
+            Error::ServerClosed { code, reason } => write!(f, "server closed the socket: {} {}", code, reason),
+            Error::Timeout => write!(f, "request timed out"),
+            Error::ConnectionClosed => write!(f, "connection closed"),
+            Error::Reqwest(e) => write!(f, "http error: {}", e),
+            Error::WebSocket(e) => write!(f, "websocket error: {}", e),
+            Error::Json(e) => write!(f, "json error: {}", e),
+            Error::Io(e) => write!(f, "io error: {}", e),
+            Error::Url(e) => write!(f, "url error: {}", e),
+            Error::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error {
+    /// True for failures that may go away on their own: timeouts, dropped connections,
+    /// server errors and throttling without a penalty ticket.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::Timeout | Error::ConnectionClosed => true,
+            Error::RateLimited { ticket, .. } => ticket.is_none(),
+            Error::Api { status, .. } => *status >= 500,
+            Error::Reqwest(e) => e.is_timeout() || e.is_connect(),
+            _ => false,
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Reqwest(e) => Some(e),
+            Error::WebSocket(e) => Some(e.as_ref()),
+            Error::Json(e) => Some(e),
+            Error::Io(e) => Some(e),
+            Error::Url(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Reqwest(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<url::ParseError> for Error {
+    fn from(e: url::ParseError) -> Self {
+        Error::Url(e)
+    }
+}
+
+impl From<tungstenite::Error> for Error {
+    fn from(e: tungstenite::Error) -> Self {
+        match e {
+            tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => Error::ConnectionClosed,
+            e => Error::WebSocket(Box::new(e)),
+        }
+    }
+}
+
+impl From<OrderValidationError> for Error {
+    fn from(e: OrderValidationError) -> Self {
+        Error::InvalidOrder(e)
+    }
+}
@@ -1,4 +1,6 @@
 pub mod client;
+pub mod config;
+pub mod client_builder;
 pub mod utils;
 #[cfg(test)]
 pub mod tests;
@@ -7,4 +9,6 @@ pub mod models;
 pub mod rest;
 pub mod error;
 pub mod websocket;
-pub mod time_utils;
\ No newline at end of file
+pub mod time_utils;
+pub mod token_manager;
+pub mod token_store;
\ No newline at end of file
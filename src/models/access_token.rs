@@ -2,6 +2,8 @@ use chrono::DateTime;
 use chrono::FixedOffset;
 use serde::Deserialize;
 use serde::Serialize;
+use crate::client::{ResourceType, Server};
+use crate::error::Error;
 use crate::utils;
 
 
@@ -27,6 +29,44 @@ impl AccessTokenInfo {
     pub fn is_expired(&self) -> bool {
         self.expiration_time < chrono::Utc::now()
     }
+    /// Time left until the token expires, zero if it already has.
+    pub fn expires_in(&self) -> std::time::Duration {
+        (self.expiration_time.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .unwrap_or_default()
+    }
+    /// Market data only logins get a market data token but no trading token.
+    pub fn has_trading(&self) -> bool {
+        !self.access_token.is_empty()
+    }
+    /// The token sockets of `resource_type` are authorized with,
+    /// market data and replay sockets take the market data token.
+    pub fn token_for(&self, resource_type: ResourceType) -> &str {
+        match resource_type {
+            ResourceType::Trading => &self.access_token,
+            ResourceType::MarketData | ResourceType::MarketReplay if !self.md_access_token.is_empty() => {
+                &self.md_access_token
+            }
+            ResourceType::MarketData | ResourceType::MarketReplay => &self.access_token,
+        }
+    }
+    /// The token is sent as is, not as a JSON body.
+    pub fn ws_auth_msg(&self, resource_type: ResourceType, request_id: usize) -> String {
+        format!("authorize\n{}\n\n{}", request_id, self.token_for(resource_type))
+    }
+    /// Fails unless the login can trade on `server_type`, live trading also needs a live, funded account.
+    pub fn check_trading(&self, server_type: Server) -> Result<(), Error> {
+        if !self.has_trading() {
+            return Err(Error::NotPermitted(format!("{} only has market data access", self.name)));
+        }
+        if server_type == Server::Live && !self.has_live {
+            return Err(Error::NotPermitted(format!("{} has no live access", self.name)));
+        }
+        if server_type == Server::Live && !self.has_funded {
+            return Err(Error::NotPermitted(format!("{} has no funded account", self.name)));
+        }
+        Ok(())
+    }
 }
 
 
@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use chrono::DateTime;
+use rust_decimal::Decimal;
+use tokio::sync::watch;
+
+use super::entity_event::{Entity, EntityEvent, EntityEventType};
+use super::execution::{Fill, Order, OrderVersion};
+use super::position::Position;
+use super::user_data::{AccountRiskStatuse, CashBalance, MarginSnapshot, UserData};
+
+#[derive(Default, Debug, Clone)]
+struct AccountData {
+    orders: HashMap<i64, Order>,
+    order_versions: HashMap<i64, OrderVersion>,
+    fills: HashMap<i64, Fill>,
+    positions: HashMap<i64, Position>,
+    cash_balances: HashMap<i64, CashBalance>,
+    margin_snapshots: HashMap<i64, MarginSnapshot>,
+    risk_statuses: HashMap<i64, AccountRiskStatuse>,
+    seeded: bool,
+    /// The events received before the snapshot, applied on top of it once it arrives.
+    unseeded_events: Vec<EntityEvent>,
+}
+impl AccountData {
+    fn from_user_data(user_data: UserData) -> Self {
+        let mut data = Self::default();
+        for order in user_data.orders {
+            data.orders.insert(order.id, order);
+        }
+        for version in user_data.order_versions {
+            data.insert_version(version);
+        }
+        for fill in user_data.fills {
+            data.fills.insert(fill.id, fill);
+        }
+        for position in user_data.positions {
+            data.positions.insert(position.id, position);
+        }
+        for balance in user_data.cash_balances {
+            data.cash_balances.insert(balance.id, balance);
+        }
+        for snapshot in user_data.margin_snapshots {
+            data.margin_snapshots.insert(snapshot.id, snapshot);
+        }
+        for status in user_data.account_risk_statuses {
+            data.risk_statuses.insert(status.id, status);
+        }
+        data.seeded = true;
+        data
+    }
+    /// Keeps the latest version of each order.
+    fn insert_version(&mut self, version: OrderVersion) {
+        let newer = self
+            .order_versions
+            .get(&version.order_id)
+            .is_none_or(|current| current.id <= version.id);
+        if newer {
+            self.order_versions.insert(version.order_id, version);
+        }
+    }
+    /// True if the state already holds a later copy of the event's entity: a later timestamp,
+    /// a later version of the order, or an order that is done while the event's copy is still working.
+    fn is_newer_than(&self, event: &EntityEvent) -> bool {
+        match &event.entity {
+            Entity::Order(order) => self.orders.get(&order.id).is_some_and(|current| {
+                current.timestamp > order.timestamp || (current.ord_status.is_done() && !order.ord_status.is_done())
+            }),
+            Entity::OrderVersion(version) => self
+                .order_versions
+                .get(&version.order_id)
+                .is_some_and(|current| current.id > version.id),
+            Entity::Fill(fill) => self.fills.get(&fill.id).is_some_and(|current| current.timestamp > fill.timestamp),
+            Entity::Position(position) => self
+                .positions
+                .get(&position.id)
+                .is_some_and(|current| current.timestamp > position.timestamp),
+            Entity::CashBalance(balance) => self
+                .cash_balances
+                .get(&balance.id)
+                .is_some_and(|current| is_later(&current.timestamp, &balance.timestamp)),
+            Entity::MarginSnapshot(snapshot) => self
+                .margin_snapshots
+                .get(&snapshot.id)
+                .is_some_and(|current| is_later(&current.timestamp, &snapshot.timestamp)),
+            _ => false,
+        }
+    }
+    fn tracks(entity: &Entity) -> bool {
+        matches!(
+            entity,
+            Entity::Order(_)
+                | Entity::OrderVersion(_)
+                | Entity::Fill(_)
+                | Entity::Position(_)
+                | Entity::CashBalance(_)
+                | Entity::MarginSnapshot(_)
+                | Entity::AccountRiskStatus(_)
+        )
+    }
+    fn apply(&mut self, event: &EntityEvent) -> bool {
+        let deleted = event.event_type == EntityEventType::Deleted;
+        macro_rules! upsert {
+            ($map:expr, $key:expr, $entity:expr) => {
+                if deleted {
+                    $map.remove(&$key);
+                } else {
+                    $map.insert($key, $entity.clone());
+                }
+            };
+        }
+        match &event.entity {
+            Entity::Order(order) => upsert!(self.orders, order.id, order),
+            Entity::OrderVersion(version) => {
+                if deleted {
+                    self.order_versions.remove(&version.order_id);
+                } else {
+                    self.insert_version(version.clone());
+                }
+            }
+            Entity::Fill(fill) => upsert!(self.fills, fill.id, fill),
+            Entity::Position(position) => upsert!(self.positions, position.id, position),
+            Entity::CashBalance(balance) => upsert!(self.cash_balances, balance.id, balance),
+            Entity::MarginSnapshot(snapshot) => upsert!(self.margin_snapshots, snapshot.id, snapshot),
+            Entity::AccountRiskStatus(status) => upsert!(self.risk_statuses, status.id, status),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Compares two RFC 3339 timestamps, false if either can't be parsed.
+fn is_later(timestamp: &str, other: &str) -> bool {
+    match (DateTime::parse_from_rfc3339(timestamp), DateTime::parse_from_rfc3339(other)) {
+        (Ok(timestamp), Ok(other)) => timestamp > other,
+        _ => false,
+    }
+}
+
+/// A local mirror of the user's orders, fills, positions and balances.
+/// It is seeded from the `user/syncrequest` snapshot and kept up to date with the `props` events
+/// of the account socket. Every clone shares the same state.
+#[derive(Debug, Clone)]
+pub struct AccountState {
+    data: Arc<RwLock<AccountData>>,
+    version: Arc<watch::Sender<u64>>,
+}
+impl Default for AccountState {
+    fn default() -> Self {
+        Self {
+            data: Arc::new(RwLock::new(AccountData::default())),
+            version: Arc::new(watch::channel(0).0),
+        }
+    }
+}
+impl AccountState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Replaces the whole state with the snapshot, then applies the events received before it.
+    /// An event older than the snapshot's copy of its entity is dropped, see `AccountData::is_newer_than`.
+    pub fn seed(&self, user_data: UserData) {
+        let mut data = self.data.write().unwrap();
+        let unseeded_events = std::mem::take(&mut data.unseeded_events);
+        *data = AccountData::from_user_data(user_data);
+        for event in &unseeded_events {
+            if !data.is_newer_than(event) {
+                data.apply(event);
+            }
+        }
+        drop(data);
+        self.version.send_modify(|version| *version += 1);
+    }
+    /// Applies a `props` event, returns false if the state does not track its kind of entity.
+    /// Until the state is seeded the event is only kept, see `seed`.
+    pub fn apply(&self, event: &EntityEvent) -> bool {
+        let mut data = self.data.write().unwrap();
+        if !data.seeded {
+            if !AccountData::tracks(&event.entity) {
+                return false;
+            }
+            data.unseeded_events.push(event.clone());
+            return true;
+        }
+        let applied = data.apply(event);
+        drop(data);
+        if applied {
+            self.version.send_modify(|version| *version += 1);
+        }
+        applied
+    }
+    /// Changes every time the state is seeded or an event is applied.
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.version.subscribe()
+    }
+    pub fn order(&self, order_id: i64) -> Option<Order> {
+        self.data.read().unwrap().orders.get(&order_id).cloned()
+    }
+    /// The current terms of the order.
+    pub fn order_version(&self, order_id: i64) -> Option<OrderVersion> {
+        self.data.read().unwrap().order_versions.get(&order_id).cloned()
+    }
+    /// The orders of the contract that can still trade.
+    pub fn working_orders(&self, contract_id: i64) -> Vec<Order> {
+        let data = self.data.read().unwrap();
+        let mut orders = data
+            .orders
+            .values()
+            .filter(|order| order.contract_id == contract_id && order.ord_status.is_working())
+            .cloned()
+            .collect::<Vec<_>>();
+        orders.sort_by_key(|order| order.id);
+        orders
+    }
+    pub fn fills(&self, order_id: i64) -> Vec<Fill> {
+        let data = self.data.read().unwrap();
+        let mut fills = data
+            .fills
+            .values()
+            .filter(|fill| fill.order_id == order_id)
+            .cloned()
+            .collect::<Vec<_>>();
+        fills.sort_by_key(|fill| fill.id);
+        fills
+    }
+    pub fn positions(&self) -> Vec<Position> {
+        self.data.read().unwrap().positions.values().cloned().collect()
+    }
+    fn position(&self, account_id: i64, contract_id: i64) -> Option<Position> {
+        self.data
+            .read()
+            .unwrap()
+            .positions
+            .values()
+            .find(|position| position.account_id == account_id && position.contract_id == contract_id)
+            .cloned()
+    }
+    /// The net position of the account in the contract, positive when long.
+    pub fn net_position(&self, account_id: i64, contract_id: i64) -> i64 {
+        self.position(account_id, contract_id).map_or(0, |position| position.net_pos)
+    }
+    /// The average price of the account's open position in the contract, `None` when flat.
+    pub fn average_price(&self, account_id: i64, contract_id: i64) -> Option<Decimal> {
+        let position = self.position(account_id, contract_id).filter(|position| position.net_pos != 0)?;
+        Decimal::try_from(position.net_price).ok()
+    }
+    /// The P&L of the account's open position in the contract if it closed at `last_price`.
+    pub fn open_pnl(&self, account_id: i64, contract_id: i64, last_price: Decimal, value_per_point: Decimal) -> Decimal {
+        let Some(position) = self.position(account_id, contract_id).filter(|position| position.net_pos != 0) else {
+            return Decimal::ZERO;
+        };
+        match Decimal::try_from(position.net_price) {
+            Ok(average_price) => (last_price - average_price) * Decimal::from(position.net_pos) * value_per_point,
+            Err(_) => Decimal::ZERO,
+        }
+    }
+    /// The P&L realized today by the account.
+    pub fn realized_pnl(&self, account_id: i64) -> Decimal {
+        self.data
+            .read()
+            .unwrap()
+            .cash_balances
+            .values()
+            .filter(|balance| balance.account_id == account_id)
+            .filter_map(|balance| Decimal::try_from(balance.realized_pn_l).ok())
+            .sum()
+    }
+    pub fn cash_balance(&self, account_id: i64) -> Option<CashBalance> {
+        self.data
+            .read()
+            .unwrap()
+            .cash_balances
+            .values()
+            .find(|balance| balance.account_id == account_id)
+            .cloned()
+    }
+    /// The margin snapshot of an account shares its id.
+    pub fn margin(&self, account_id: i64) -> Option<MarginSnapshot> {
+        self.data.read().unwrap().margin_snapshots.get(&account_id).cloned()
+    }
+    /// The risk status of an account shares its id.
+    pub fn risk_status(&self, account_id: i64) -> Option<AccountRiskStatuse> {
+        self.data.read().unwrap().risk_statuses.get(&account_id).cloned()
+    }
+}
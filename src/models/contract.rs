@@ -42,7 +42,7 @@ where
 {
     let s: String = Deserialize::deserialize(deserializer)?;
     let dt = chrono::NaiveDateTime::parse_from_str(&s, "%Y-%m-%dT%H:%M%Z").map_err(de::Error::custom)?;
-    Ok(chrono::DateTime::<FixedOffset>::from_utc(dt, FixedOffset::east_opt(0).unwrap()))
+    Ok(chrono::DateTime::<FixedOffset>::from_naive_utc_and_offset(dt, FixedOffset::east_opt(0).unwrap()))
 
 }
     
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::execution::{Command, CommandReport, ExecutionReport, Fill, FillPair, Order, OrderVersion};
+use super::order_strategy::{OrderStrategy, OrderStrategyLink};
+use super::position::Position;
+use super::user_data::{AccountRiskStatuse, CashBalance, MarginSnapshot};
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EntityEventType {
+    Created,
+    Updated,
+    Deleted,
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+/// An entity pushed by the account socket, the kinds the crate does not model are kept as json.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Entity {
+    Order(Order),
+    OrderVersion(OrderVersion),
+    Command(Command),
+    CommandReport(CommandReport),
+    ExecutionReport(ExecutionReport),
+    Fill(Fill),
+    FillPair(FillPair),
+    Position(Position),
+    CashBalance(CashBalance),
+    MarginSnapshot(MarginSnapshot),
+    AccountRiskStatus(AccountRiskStatuse),
+    OrderStrategy(OrderStrategy),
+    OrderStrategyLink(OrderStrategyLink),
+    Other(Value),
+}
+impl Entity {
+    pub fn from_value(entity_type: &str, entity: Value) -> Result<Self, serde_json::Error> {
+        Ok(match entity_type {
+            "order" => Entity::Order(serde_json::from_value(entity)?),
+            "orderVersion" => Entity::OrderVersion(serde_json::from_value(entity)?),
+            "command" => Entity::Command(serde_json::from_value(entity)?),
+            "commandReport" => Entity::CommandReport(serde_json::from_value(entity)?),
+            "executionReport" => Entity::ExecutionReport(serde_json::from_value(entity)?),
+            "fill" => Entity::Fill(serde_json::from_value(entity)?),
+            "fillPair" => Entity::FillPair(serde_json::from_value(entity)?),
+            "position" => Entity::Position(serde_json::from_value(entity)?),
+            "cashBalance" => Entity::CashBalance(serde_json::from_value(entity)?),
+            "marginSnapshot" => Entity::MarginSnapshot(serde_json::from_value(entity)?),
+            "accountRiskStatus" => Entity::AccountRiskStatus(serde_json::from_value(entity)?),
+            "orderStrategy" => Entity::OrderStrategy(serde_json::from_value(entity)?),
+            "orderStrategyLink" => Entity::OrderStrategyLink(serde_json::from_value(entity)?),
+            _ => Entity::Other(entity),
+        })
+    }
+    pub fn id(&self) -> i64 {
+        match self {
+            Entity::Order(order) => order.id,
+            Entity::OrderVersion(version) => version.id,
+            Entity::Command(command) => command.id,
+            Entity::CommandReport(report) => report.id,
+            Entity::ExecutionReport(report) => report.id,
+            Entity::Fill(fill) => fill.id,
+            Entity::FillPair(pair) => pair.id,
+            Entity::Position(position) => position.id,
+            Entity::CashBalance(balance) => balance.id,
+            Entity::MarginSnapshot(snapshot) => snapshot.id,
+            Entity::AccountRiskStatus(status) => status.id,
+            Entity::OrderStrategy(strategy) => strategy.id,
+            Entity::OrderStrategyLink(link) => link.id,
+            Entity::Other(entity) => entity.get("id").and_then(Value::as_i64).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawEntityEvent {
+    entity_type: String,
+    #[serde(default)]
+    event_type: EntityEventType,
+    entity: Value,
+}
+
+/// A `props` event of the account socket: an entity of the user was created, updated or deleted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityEvent {
+    pub entity_type: String,
+    pub event_type: EntityEventType,
+    pub entity: Entity,
+}
+impl EntityEvent {
+    pub fn from_props(data: Value) -> Result<Self, serde_json::Error> {
+        let raw = serde_json::from_value::<RawEntityEvent>(data)?;
+        Ok(Self {
+            entity: Entity::from_value(&raw.entity_type, raw.entity)?,
+            entity_type: raw.entity_type,
+            event_type: raw.event_type,
+        })
+    }
+}
@@ -0,0 +1,221 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::orders::{OrderAction, OrderType, TimeInForce};
+use super::position::parse_date;
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrdStatus {
+    Canceled,
+    Completed,
+    Expired,
+    Filled,
+    PendingCancel,
+    PendingNew,
+    PendingReplace,
+    Rejected,
+    Suspended,
+    Working,
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+impl OrdStatus {
+    /// True while the order can still trade.
+    pub fn is_working(&self) -> bool {
+        matches!(
+            self,
+            OrdStatus::Working | OrdStatus::PendingNew | OrdStatus::PendingReplace | OrdStatus::PendingCancel | OrdStatus::Suspended
+        )
+    }
+    /// True once nothing more will happen to the order.
+    pub fn is_done(&self) -> bool {
+        matches!(
+            self,
+            OrdStatus::Canceled | OrdStatus::Completed | OrdStatus::Expired | OrdStatus::Filled | OrdStatus::Rejected
+        )
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExecType {
+    New,
+    Canceled,
+    Completed,
+    DoneForDay,
+    Expired,
+    PendingCancel,
+    PendingNew,
+    PendingReplace,
+    Rejected,
+    Replaced,
+    Stopped,
+    Suspended,
+    Trade,
+    TradeCancel,
+    TradeCorrect,
+    TriggeredUpdate,
+    OrderStatus,
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CommandType {
+    New,
+    Modify,
+    Cancel,
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CommandStatus {
+    AtExecution,
+    ExecutionRejected,
+    ExecutionStopped,
+    ExecutionSuspended,
+    OnHold,
+    Pending,
+    PendingExecution,
+    Replaced,
+    RiskPassed,
+    RiskRejected,
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+impl CommandStatus {
+    pub fn is_rejected(&self) -> bool {
+        matches!(self, CommandStatus::ExecutionRejected | CommandStatus::RiskRejected)
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct Order {
+    pub id: i64,
+    pub account_id: i64,
+    pub contract_id: i64,
+    pub spread_definition_id: Option<i64>,
+    pub timestamp: DateTime<Utc>,
+    pub action: OrderAction,
+    pub ord_status: OrdStatus,
+    pub execution_provider_id: Option<i64>,
+    pub oco_id: Option<i64>,
+    pub parent_id: Option<i64>,
+    pub linked_id: Option<i64>,
+    pub admin: bool,
+}
+
+/// The terms of an order, a new version is created every time the order is modified.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct OrderVersion {
+    pub id: i64,
+    pub order_id: i64,
+    pub order_qty: i64,
+    pub order_type: OrderType,
+    pub price: Option<Decimal>,
+    pub stop_price: Option<Decimal>,
+    pub max_show: Option<i64>,
+    pub peg_difference: Option<Decimal>,
+    pub time_in_force: Option<TimeInForce>,
+    pub expire_time: Option<DateTime<Utc>>,
+    pub text: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct Fill {
+    pub id: i64,
+    pub order_id: i64,
+    pub contract_id: i64,
+    pub timestamp: DateTime<Utc>,
+    #[serde(deserialize_with = "parse_date")]
+    pub trade_date: NaiveDate,
+    pub action: OrderAction,
+    pub qty: i64,
+    pub price: Decimal,
+    pub active: bool,
+    pub finally_paired: i64,
+}
+
+/// A buy and a sell fill matched against each other, closing `qty` contracts of a position.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct FillPair {
+    pub id: i64,
+    pub position_id: i64,
+    pub buy_fill_id: i64,
+    pub sell_fill_id: i64,
+    pub qty: i64,
+    pub buy_price: Decimal,
+    pub sell_price: Decimal,
+    pub active: bool,
+}
+
+/// What the exchange did with an order, every change of status and every trade has one.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct ExecutionReport {
+    pub id: i64,
+    pub command_id: i64,
+    pub name: String,
+    pub account_id: i64,
+    pub contract_id: i64,
+    pub timestamp: DateTime<Utc>,
+    #[serde(deserialize_with = "parse_date")]
+    pub trade_date: NaiveDate,
+    pub order_id: i64,
+    pub exec_type: ExecType,
+    pub exec_ref_id: Option<String>,
+    pub ord_status: Option<OrdStatus>,
+    pub action: OrderAction,
+    pub cum_qty: Option<i64>,
+    pub avg_px: Option<Decimal>,
+    pub last_qty: Option<i64>,
+    pub last_px: Option<Decimal>,
+    pub reject_reason: Option<String>,
+    pub text: Option<String>,
+    pub exchange_order_id: Option<String>,
+}
+
+/// A request sent for an order: placing, modifying or cancelling it.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct Command {
+    pub id: i64,
+    pub order_id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub cl_ord_id: Option<String>,
+    pub command_type: CommandType,
+    pub command_status: CommandStatus,
+    pub sender_id: Option<i64>,
+    pub user_session_id: Option<i64>,
+    pub activation_time: Option<DateTime<Utc>>,
+    pub custom_tag50: Option<String>,
+    pub is_automated: Option<bool>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct CommandReport {
+    pub id: i64,
+    pub command_id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub command_status: CommandStatus,
+    pub reject_reason: Option<String>,
+    pub text: Option<String>,
+    pub ord_status: Option<OrdStatus>,
+}
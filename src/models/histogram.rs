@@ -0,0 +1,149 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer, Serialize};
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct Histograms {
+    pub histograms: Vec<Histogram>,
+}
+
+/// Volume traded at each price of the session.
+/// Prices are kept as offsets, in ticks, from `base`: the price of offset `n` is `base + n * tick_size`.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct Histogram {
+    pub contract_id: i64,
+    #[serde(deserialize_with = "parse_timestamp")]
+    pub timestamp: DateTime<Utc>,
+    pub trade_date: TradeDate,
+    pub base: Decimal,
+    pub items: BTreeMap<i64, Decimal>,
+    /// When true the message carries the whole histogram instead of the changed items.
+    pub refresh: bool,
+}
+impl Histogram {
+    /// A refresh replaces the histogram, any other update overwrites the volumes of the offsets it carries.
+    /// When the update is relative to a different base the existing offsets are moved onto it first,
+    /// which needs the contract's `tick_size`. Without it, or if the bases are not a whole number of
+    /// ticks apart, the update replaces the histogram.
+    pub fn merge(&mut self, update: Histogram, tick_size: Option<Decimal>) {
+        if update.refresh {
+            *self = update;
+            return;
+        }
+        if update.base != self.base {
+            match tick_size.and_then(|tick_size| self.rebase(update.base, tick_size)) {
+                Some(rebased) => *self = rebased,
+                None => {
+                    *self = update;
+                    return;
+                }
+            }
+        }
+        self.contract_id = update.contract_id;
+        self.timestamp = update.timestamp;
+        self.trade_date = update.trade_date;
+        self.items.extend(update.items);
+    }
+    /// The same histogram with its offsets relative to `base`.
+    pub fn rebase(&self, base: Decimal, tick_size: Decimal) -> Option<Histogram> {
+        if tick_size.is_zero() {
+            return None;
+        }
+        let shift = (self.base - base) / tick_size;
+        if !shift.fract().is_zero() {
+            return None;
+        }
+        let shift = i64::try_from(shift).ok()?;
+        Some(Histogram {
+            base,
+            items: self.items.iter().map(|(offset, volume)| (offset + shift, *volume)).collect(),
+            ..self.clone()
+        })
+    }
+    pub fn price_at(&self, offset: i64, tick_size: Decimal) -> Decimal {
+        self.base + Decimal::from(offset) * tick_size
+    }
+    pub fn volume_at(&self, price: Decimal, tick_size: Decimal) -> Decimal {
+        let offset = ((price - self.base) / tick_size).round();
+        i64::try_from(offset)
+            .ok()
+            .and_then(|offset| self.items.get(&offset).copied())
+            .unwrap_or_default()
+    }
+    /// The highest price with volume.
+    pub fn high(&self, tick_size: Decimal) -> Option<Decimal> {
+        self.items
+            .iter()
+            .rev()
+            .find(|(_, volume)| !volume.is_zero())
+            .map(|(offset, _)| self.price_at(*offset, tick_size))
+    }
+    /// The lowest price with volume.
+    pub fn low(&self, tick_size: Decimal) -> Option<Decimal> {
+        self.items
+            .iter()
+            .find(|(_, volume)| !volume.is_zero())
+            .map(|(offset, _)| self.price_at(*offset, tick_size))
+    }
+    /// The price with the most volume.
+    pub fn point_of_control(&self, tick_size: Decimal) -> Option<Decimal> {
+        self.items
+            .iter()
+            .max_by_key(|(_, volume)| **volume)
+            .map(|(offset, _)| self.price_at(*offset, tick_size))
+    }
+    pub fn total_volume(&self) -> Decimal {
+        self.items.values().sum()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeDate {
+    pub year: i64,
+    pub month: i64,
+    pub day: i64,
+}
+
+/// The merged histogram of every contract.
+/// Set the tick size of a contract to keep its histogram when the server moves its base, see `Histogram::merge`.
+#[derive(Debug, Clone, Default)]
+pub struct HistogramCache {
+    histograms: Arc<std::sync::RwLock<HashMap<i64, Histogram>>>,
+    tick_sizes: Arc<std::sync::RwLock<HashMap<i64, Decimal>>>,
+}
+impl HistogramCache {
+    pub fn set_tick_size(&self, contract_id: i64, tick_size: Decimal) {
+        self.tick_sizes.write().unwrap().insert(contract_id, tick_size);
+    }
+    /// Merges the update into the contract's histogram and returns the result.
+    pub fn update(&self, histogram: Histogram) -> Histogram {
+        let tick_size = self.tick_sizes.read().unwrap().get(&histogram.contract_id).copied();
+        let mut histograms = self.histograms.write().unwrap();
+        let merged = histograms
+            .entry(histogram.contract_id)
+            .and_modify(|last| last.merge(histogram.clone(), tick_size))
+            .or_insert(histogram);
+        merged.clone()
+    }
+    pub fn get(&self, contract_id: i64) -> Option<Histogram> {
+        self.histograms.read().unwrap().get(&contract_id).cloned()
+    }
+}
+
+//"2022-09-15T00:00:58.230Z"
+fn parse_timestamp<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    let dt = DateTime::parse_from_rfc3339(&s).map_err(serde::de::Error::custom)?;
+    Ok(dt.with_timezone(&Utc))
+}
@@ -10,3 +10,9 @@ pub mod replay_clock;
 pub mod orders;
 pub mod account;
 pub mod user_data;
+pub mod shutdown;
+pub mod histogram;
+pub mod order_strategy;
+pub mod execution;
+pub mod entity_event;
+pub mod account_state;
@@ -0,0 +1,276 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::orders::{CommandResponse, OrderAction, OrderType, OrderValidationError, TimeInForce};
+
+/// The id of the built in "multibracket" strategy type, the only one Tradovate exposes.
+pub const MULTI_BRACKET_STRATEGY_TYPE_ID: i64 = 2;
+
+/// The parameters of a multi-bracket strategy: how to enter, then how to exit each part of the position.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct OrderStrategyParams {
+    pub entry_version: EntryVersion,
+    pub brackets: Vec<StrategyBracket>,
+}
+impl OrderStrategyParams {
+    pub fn new(entry_version: EntryVersion) -> Self {
+        Self {
+            entry_version,
+            brackets: Vec::new(),
+        }
+    }
+    pub fn bracket(mut self, bracket: StrategyBracket) -> Self {
+        self.brackets.push(bracket);
+        self
+    }
+    /// The server expects the parameters as a JSON encoded string.
+    pub fn to_params_string(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct EntryVersion {
+    pub order_qty: i64,
+    pub order_type: OrderType,
+    pub time_in_force: Option<TimeInForce>,
+    pub price: Option<Decimal>,
+    pub stop_price: Option<Decimal>,
+}
+impl EntryVersion {
+    pub fn market(order_qty: i64) -> Self {
+        Self {
+            order_qty,
+            order_type: OrderType::Market,
+            ..Default::default()
+        }
+    }
+    pub fn limit(order_qty: i64, price: Decimal) -> Self {
+        Self {
+            order_qty,
+            order_type: OrderType::Limit,
+            price: Some(price),
+            ..Default::default()
+        }
+    }
+}
+
+/// One exit of the position. Targets and stops are offsets in points from the entry price,
+/// positive above it and negative below it.
+#[serde_with::skip_serializing_none]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct StrategyBracket {
+    pub qty: i64,
+    pub profit_target: Option<Decimal>,
+    pub stop_loss: Option<Decimal>,
+    /// Trail the stop behind the price from the start.
+    pub trailing_stop: bool,
+    pub auto_trail: Option<AutoTrail>,
+}
+impl StrategyBracket {
+    /// A bracket of `qty` contracts with its target and stop `target` and `stop` points away
+    /// from the entry, on the side that is right for `action`, which must be Buy or Sell.
+    pub fn new(action: &OrderAction, qty: i64, target: Decimal, stop: Decimal) -> Result<Self, OrderValidationError> {
+        let direction = match action {
+            OrderAction::Buy => Decimal::ONE,
+            OrderAction::Sell => Decimal::NEGATIVE_ONE,
+            OrderAction::Dont => return Err(OrderValidationError::MissingAction),
+        };
+        Ok(Self {
+            qty,
+            profit_target: Some(direction * target.abs()),
+            stop_loss: Some(-direction * stop.abs()),
+            ..Default::default()
+        })
+    }
+    pub fn trailing_stop(mut self, trailing_stop: bool) -> Self {
+        self.trailing_stop = trailing_stop;
+        self
+    }
+    pub fn auto_trail(mut self, auto_trail: AutoTrail) -> Self {
+        self.auto_trail = Some(auto_trail);
+        self
+    }
+}
+
+/// Starts trailing the stop once the price moved `trigger` points in favour of the position,
+/// keeping it `stop_loss` points behind and moving it in steps of `freq` points.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct AutoTrail {
+    pub stop_loss: Decimal,
+    pub trigger: Decimal,
+    pub freq: Decimal,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+/// The payload of the orderStrategy/startOrderStrategy endpoint.
+pub struct StartOrderStrategy {
+    pub account_id: i64,
+    pub account_spec: String,
+    pub symbol: String,
+    pub order_strategy_type_id: i64,
+    pub action: OrderAction,
+    /// `OrderStrategyParams` encoded as a string.
+    pub params: String,
+    pub uuid: Option<String>,
+    pub custom_tag50: Option<String>,
+}
+impl StartOrderStrategy {
+    pub fn multi_bracket(username:&str,account_id:i64,symbol:&str,action:OrderAction,params:&OrderStrategyParams) -> Self {
+        Self {
+            account_id,
+            account_spec: username.to_string(),
+            symbol: symbol.to_string(),
+            order_strategy_type_id: MULTI_BRACKET_STRATEGY_TYPE_ID,
+            action,
+            params: params.to_params_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+/// The payload of the orderStrategy/modifyOrderStrategy endpoint.
+pub struct ModifyOrderStrategy {
+    pub order_strategy_id: i64,
+    /// The new parameters encoded as a string.
+    pub command: String,
+    pub custom_tag50: Option<String>,
+}
+impl ModifyOrderStrategy {
+    pub fn new(order_strategy_id:i64,params:&OrderStrategyParams) -> Self {
+        Self {
+            order_strategy_id,
+            command: params.to_params_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+/// The payload of the orderStrategy/interruptOrderStrategy endpoint.
+pub struct InterruptOrderStrategy {
+    pub order_strategy_id: i64,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum OrderStrategyStatus {
+    ActiveStrategy,
+    ExecutionFailed,
+    ExecutionFinished,
+    ExecutionInterrupted,
+    InactiveStrategy,
+    NotEnoughLiquidity,
+    StoppedByUser,
+    /// A status this crate does not know of, or none at all.
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+impl OrderStrategyStatus {
+    pub fn is_active(&self) -> bool {
+        self == &OrderStrategyStatus::ActiveStrategy
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct OrderStrategy {
+    pub id: i64,
+    pub account_id: i64,
+    pub timestamp: String,
+    pub contract_id: i64,
+    pub order_strategy_type_id: i64,
+    pub initiator_id: Option<i64>,
+    pub action: OrderAction,
+    pub params: Option<String>,
+    pub uuid: Option<String>,
+    pub status: OrderStrategyStatus,
+    pub failure_message: Option<String>,
+    pub sender_id: Option<i64>,
+    pub custom_tag50: Option<String>,
+    pub user_session_id: Option<i64>,
+}
+impl OrderStrategy {
+    /// The parameters the strategy was started with.
+    pub fn params(&self) -> Option<OrderStrategyParams> {
+        serde_json::from_str(self.params.as_deref()?).ok()
+    }
+}
+
+/// Links an order to the strategy that placed it, `label` tells which part of the strategy it is.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct OrderStrategyLink {
+    pub id: i64,
+    pub order_strategy_id: i64,
+    pub order_id: i64,
+    pub label: String,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+/// The response to startOrderStrategy, a `failure_reason` means it was refused.
+pub struct OrderStrategyResult {
+    pub order_strategy: Option<OrderStrategy>,
+    pub failure_reason: Option<String>,
+    pub failure_text: Option<String>,
+}
+impl OrderStrategyResult {
+    pub fn is_ok(&self) -> bool {
+        self.failure_reason.is_none()
+    }
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+/// The response to modifyOrderStrategy and interruptOrderStrategy.
+pub struct OrderStrategyStatusResult {
+    pub order_strategy: Option<OrderStrategy>,
+    pub error_text: Option<String>,
+}
+impl OrderStrategyStatusResult {
+    pub fn is_ok(&self) -> bool {
+        self.error_text.is_none()
+    }
+}
+impl CommandResponse for OrderStrategyResult {
+    fn failure_reason(&self) -> Option<&str> {
+        self.failure_reason.as_deref()
+    }
+    fn failure_text(&self) -> Option<&str> {
+        self.failure_text.as_deref()
+    }
+}
+/// The server only sends an `error_text`, it is reported as the reason.
+impl CommandResponse for OrderStrategyStatusResult {
+    fn failure_reason(&self) -> Option<&str> {
+        self.error_text.as_deref()
+    }
+    fn failure_text(&self) -> Option<&str> {
+        None
+    }
+}
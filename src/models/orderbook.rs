@@ -1,4 +1,5 @@
 
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
 use chrono::DateTime;
@@ -101,10 +102,116 @@ where
     let s: String = Deserialize::deserialize(deserializer)?;
     let dt = chrono::NaiveDateTime::parse_from_str(&s, "%Y-%m-%dT%H:%M:%S%.fZ")
         .map_err(de::Error::custom)?;
-    Ok(chrono::DateTime::<Utc>::from_utc(dt, Utc))
+    Ok(chrono::DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc))
 }
 
 
 pub fn new_orderbooks_rwl() -> Arc<RwLock<Vec<OrderBooks>>> {
     Arc::new(RwLock::new(Vec::new()))
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookSide {
+    Bid,
+    Ask,
+}
+
+/// The order book of one contract, maintained from its DOM updates.
+/// Levels are kept price-indexed, so the best price is found in O(log n).
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct LiveOrderBook {
+    pub contract_id: i64,
+    pub timestamp: DateTime<Utc>,
+    bids: BTreeMap<Decimal, i64>,
+    asks: BTreeMap<Decimal, i64>,
+}
+impl LiveOrderBook {
+    pub fn new(contract_id: i64) -> Self {
+        Self {
+            contract_id,
+            ..Default::default()
+        }
+    }
+    /// DOM messages are snapshots of the top levels of both sides, so the book is rebuilt
+    /// from the message and levels that fell out of it are dropped. Levels with a size of zero are skipped.
+    pub fn apply(&mut self, update: &OrderBook) {
+        self.contract_id = update.contract_id;
+        self.timestamp = update.timestamp;
+        self.bids = snapshot_levels(&update.bids);
+        self.asks = snapshot_levels(&update.asks);
+    }
+    fn levels(&self, side: BookSide) -> Box<dyn Iterator<Item = (&Decimal, &i64)> + '_> {
+        match side {
+            BookSide::Bid => Box::new(self.bids.iter().rev()),
+            BookSide::Ask => Box::new(self.asks.iter()),
+        }
+    }
+    pub fn best_bid(&self) -> Option<Depth> {
+        self.depth(BookSide::Bid, 1).pop()
+    }
+    pub fn best_ask(&self) -> Option<Depth> {
+        self.depth(BookSide::Ask, 1).pop()
+    }
+    pub fn spread(&self) -> Option<Decimal> {
+        Some(self.best_ask()?.price - self.best_bid()?.price)
+    }
+    /// The best `levels` levels of the side, best price first.
+    pub fn depth(&self, side: BookSide, levels: usize) -> Vec<Depth> {
+        self.levels(side)
+            .take(levels)
+            .map(|(price, size)| Depth { price: *price, size: *size })
+            .collect()
+    }
+    /// The size resting on the best `levels` levels of the side.
+    pub fn cumulative_size(&self, side: BookSide, levels: usize) -> i64 {
+        self.levels(side).take(levels).map(|(_, size)| size).sum()
+    }
+    /// `(bids - asks) / (bids + asks)` over the best `levels` levels, from -1 (only asks) to 1 (only bids).
+    pub fn imbalance(&self, levels: usize) -> Option<Decimal> {
+        let bids = self.cumulative_size(BookSide::Bid, levels);
+        let asks = self.cumulative_size(BookSide::Ask, levels);
+        if bids + asks == 0 {
+            return None;
+        }
+        Some(Decimal::from(bids - asks) / Decimal::from(bids + asks))
+    }
+    pub fn is_empty(&self) -> bool {
+        self.bids.is_empty() && self.asks.is_empty()
+    }
+    /// A snapshot in the shape of a DOM message, bids and asks best price first.
+    pub fn to_orderbook(&self) -> OrderBook {
+        OrderBook {
+            contract_id: self.contract_id,
+            timestamp: self.timestamp,
+            bids: self.depth(BookSide::Bid, usize::MAX),
+            asks: self.depth(BookSide::Ask, usize::MAX),
+        }
+    }
+}
+
+fn snapshot_levels(levels: &[Depth]) -> BTreeMap<Decimal, i64> {
+    levels
+        .iter()
+        .filter(|level| level.size > 0)
+        .map(|level| (level.price, level.size))
+        .collect()
+}
+
+/// The live order book of every contract.
+#[derive(Debug, Clone, Default)]
+pub struct OrderBookCache {
+    books: Arc<std::sync::RwLock<HashMap<i64, Arc<LiveOrderBook>>>>,
+}
+impl OrderBookCache {
+    /// Applies the DOM update to the contract's book and returns the result.
+    pub fn update(&self, update: &OrderBook) -> Arc<LiveOrderBook> {
+        let mut book = LiveOrderBook::new(update.contract_id);
+        book.apply(update);
+        let book = Arc::new(book);
+        self.books.write().unwrap().insert(update.contract_id, book.clone());
+        book
+    }
+    pub fn get(&self, contract_id: i64) -> Option<Arc<LiveOrderBook>> {
+        self.books.read().unwrap().get(&contract_id).cloned()
+    }
+}
@@ -1,10 +1,14 @@
 
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
 use rust_decimal::Decimal;
 
+use super::product::Product;
+use crate::error::Error;
+
 /// The `OrderAction` enum is used to specify the action of an order.
 /// The default is an erroneous "Dont" to prevent accidental orders being sent
-/// from the default build.
+/// from the default build, `OrderTicket::validate` refuses it.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize,Default)]
 pub enum OrderAction {
     Buy,
@@ -12,6 +16,73 @@ pub enum OrderAction {
     #[default]
     Dont,
 }
+impl OrderAction {
+    /// The action that closes a position opened with this one.
+    pub fn opposite(&self) -> Self {
+        match self {
+            OrderAction::Buy => OrderAction::Sell,
+            OrderAction::Sell => OrderAction::Buy,
+            OrderAction::Dont => OrderAction::Dont,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum OrderType {
+    #[default]
+    Market,
+    Limit,
+    Stop,
+    StopLimit,
+    TrailingStop,
+    TrailingStopLimit,
+    /// Market if touched.
+    #[serde(rename = "MIT")]
+    Mit,
+    #[serde(rename = "QTS")]
+    Qts,
+}
+impl OrderType {
+    pub fn needs_price(&self) -> bool {
+        matches!(self, OrderType::Limit | OrderType::StopLimit | OrderType::TrailingStopLimit)
+    }
+    pub fn needs_stop_price(&self) -> bool {
+        matches!(
+            self,
+            OrderType::Stop | OrderType::StopLimit | OrderType::TrailingStop | OrderType::TrailingStopLimit | OrderType::Mit
+        )
+    }
+    /// Orders that trade at a better price than the market, a limit or a market if touched.
+    pub fn is_target(&self) -> bool {
+        matches!(self, OrderType::Limit | OrderType::Mit)
+    }
+    /// Orders that trade at a worse price than the market once their stop price is reached,
+    /// a stop limit included.
+    pub fn is_stop(&self) -> bool {
+        matches!(
+            self,
+            OrderType::Stop | OrderType::StopLimit | OrderType::TrailingStop | OrderType::TrailingStopLimit
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum TimeInForce {
+    #[default]
+    Day,
+    /// Good till cancelled.
+    #[serde(rename = "GTC")]
+    Gtc,
+    /// Good till `expire_time`.
+    #[serde(rename = "GTD")]
+    Gtd,
+    /// Immediate or cancel.
+    #[serde(rename = "IOC")]
+    Ioc,
+    /// Fill or kill.
+    #[serde(rename = "FOK")]
+    Fok,
+}
 
 #[serde_with::skip_serializing_none]
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -26,12 +97,12 @@ pub struct OrderTicket {
     pub action: OrderAction,
     pub symbol: String,
     pub order_qty: i64,
-    pub order_type: String,
+    pub order_type: OrderType,
     pub price: Option<Decimal>,
     pub stop_price: Option<Decimal>,
     pub max_show: Option<Decimal>,
     pub peg_difference: Option<Decimal>,
-    pub time_in_force: Option<String>,
+    pub time_in_force: Option<TimeInForce>,
     pub expire_time: Option<String>,
     pub text: Option<String>,
     pub activation_time: Option<String>,
@@ -47,7 +118,7 @@ impl OrderTicket {
             action: OrderAction::Buy,
             symbol: symbol.to_string(),
             order_qty: qty,
-            order_type: "Market".to_string(),
+            order_type: OrderType::Market,
             is_automated: true,
             ..Default::default()
         }
@@ -59,9 +130,558 @@ impl OrderTicket {
             action : OrderAction::Sell,
             symbol: symbol.to_string(),
             order_qty: qty,
-            order_type: "Market".to_string(),
+            order_type: OrderType::Market,
+            is_automated: true,
+            ..Default::default()
+        }
+    }
+    /// Starts an order for `symbol`, `build` refuses it until every field its type needs is set.
+    pub fn builder(username:&str,account_id:i64,symbol:&str) -> OrderTicketBuilder {
+        OrderTicketBuilder {
+            ticket: Self {
+                account_spec: username.to_string(),
+                account_id,
+                symbol: symbol.to_string(),
+                is_automated: true,
+                ..Default::default()
+            },
+            product: None,
+        }
+    }
+    /// Checks that the order has an action, a quantity and every field its type and time in force need.
+    pub fn validate(&self) -> Result<(), OrderValidationError> {
+        if self.action == OrderAction::Dont {
+            return Err(OrderValidationError::MissingAction);
+        }
+        if self.symbol.is_empty() {
+            return Err(OrderValidationError::MissingSymbol);
+        }
+        if self.order_qty <= 0 {
+            return Err(OrderValidationError::InvalidQuantity(self.order_qty));
+        }
+        check_order_fields(self.order_type, self.price, self.stop_price, self.time_in_force, &self.expire_time)
+    }
+    /// Rounds the price and stop price to the nearest tick of the product.
+    ///
+    /// # Panics
+    ///
+    /// If the product's tick size is zero, `OrderTicketBuilder::round_to` checks it instead.
+    pub fn round_to_tick(&mut self, product:&Product) {
+        self.price = self.price.map(|price| product.round_price_to_nearest_tick(price));
+        self.stop_price = self.stop_price.map(|price| product.round_price_to_nearest_tick(price));
+    }
+}
+
+/// Builds an `OrderTicket`, see `OrderTicket::builder`.
+#[derive(Debug, Clone)]
+pub struct OrderTicketBuilder {
+    ticket: OrderTicket,
+    product: Option<Product>,
+}
+impl OrderTicketBuilder {
+    pub fn buy(mut self, qty:i64) -> Self {
+        self.ticket.action = OrderAction::Buy;
+        self.ticket.order_qty = qty;
+        self
+    }
+    pub fn sell(mut self, qty:i64) -> Self {
+        self.ticket.action = OrderAction::Sell;
+        self.ticket.order_qty = qty;
+        self
+    }
+    pub fn order_type(mut self, order_type:OrderType) -> Self {
+        self.ticket.order_type = order_type;
+        self
+    }
+    pub fn market(self) -> Self {
+        self.order_type(OrderType::Market)
+    }
+    pub fn limit(mut self, price:Decimal) -> Self {
+        self.ticket.price = Some(price);
+        self.order_type(OrderType::Limit)
+    }
+    pub fn stop(mut self, stop_price:Decimal) -> Self {
+        self.ticket.stop_price = Some(stop_price);
+        self.order_type(OrderType::Stop)
+    }
+    pub fn stop_limit(mut self, stop_price:Decimal, price:Decimal) -> Self {
+        self.ticket.stop_price = Some(stop_price);
+        self.ticket.price = Some(price);
+        self.order_type(OrderType::StopLimit)
+    }
+    pub fn price(mut self, price:Decimal) -> Self {
+        self.ticket.price = Some(price);
+        self
+    }
+    pub fn stop_price(mut self, stop_price:Decimal) -> Self {
+        self.ticket.stop_price = Some(stop_price);
+        self
+    }
+    pub fn time_in_force(mut self, time_in_force:TimeInForce) -> Self {
+        self.ticket.time_in_force = Some(time_in_force);
+        self
+    }
+    /// Good till `expire_time`.
+    pub fn good_till(mut self, expire_time:DateTime<Utc>) -> Self {
+        self.ticket.expire_time = Some(expire_time.to_rfc3339_opts(SecondsFormat::Millis, true));
+        self.time_in_force(TimeInForce::Gtd)
+    }
+    pub fn cl_ord_id(mut self, cl_ord_id:&str) -> Self {
+        self.ticket.cl_ord_id = Some(cl_ord_id.to_string());
+        self
+    }
+    pub fn text(mut self, text:&str) -> Self {
+        self.ticket.text = Some(text.to_string());
+        self
+    }
+    /// Rounds the prices to the nearest tick of `product` when building.
+    /// `build` refuses a product whose tick size is not above zero.
+    pub fn round_to(mut self, product:&Product) -> Self {
+        self.product = Some(product.clone());
+        self
+    }
+    pub fn build(self) -> Result<OrderTicket, OrderValidationError> {
+        let mut ticket = self.ticket;
+        if let Some(product) = &self.product {
+            if product.tick_size <= Decimal::ZERO {
+                return Err(OrderValidationError::InvalidTickSize(product.tick_size));
+            }
+            ticket.round_to_tick(product);
+        }
+        ticket.validate()?;
+        Ok(ticket)
+    }
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+/// The payload of the order/modifyorder endpoint, the fields left as `None` keep their current value.
+pub struct ModifyOrder {
+    pub order_id: i64,
+    pub cl_ord_id: Option<String>,
+    pub order_qty: i64,
+    pub order_type: OrderType,
+    pub price: Option<Decimal>,
+    pub stop_price: Option<Decimal>,
+    pub max_show: Option<Decimal>,
+    pub peg_difference: Option<Decimal>,
+    pub time_in_force: Option<TimeInForce>,
+    pub expire_time: Option<String>,
+    pub text: Option<String>,
+    pub activation_time: Option<String>,
+    pub custom_tag50: Option<String>,
+    pub is_automated: bool,
+}
+impl ModifyOrder {
+    /// A modification of `order_id` keeping its quantity and type, set the fields to change on the result.
+    pub fn new(order_id:i64,order_qty:i64,order_type:OrderType) -> Self {
+        Self {
+            order_id,
+            order_qty,
+            order_type,
+            is_automated: true,
+            ..Default::default()
+        }
+    }
+    pub fn price(mut self, price: Decimal) -> Self {
+        self.price = Some(price);
+        self
+    }
+    pub fn stop_price(mut self, stop_price: Decimal) -> Self {
+        self.stop_price = Some(stop_price);
+        self
+    }
+    pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = Some(time_in_force);
+        self
+    }
+    pub fn validate(&self) -> Result<(), OrderValidationError> {
+        if self.order_qty <= 0 {
+            return Err(OrderValidationError::InvalidQuantity(self.order_qty));
+        }
+        check_order_fields(self.order_type, self.price, self.stop_price, self.time_in_force, &self.expire_time)
+    }
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+/// The payload of the order/cancelorder endpoint.
+pub struct CancelOrder {
+    pub order_id: i64,
+    pub cl_ord_id: Option<String>,
+    pub activation_time: Option<String>,
+    pub custom_tag50: Option<String>,
+    pub is_automated: bool,
+}
+impl CancelOrder {
+    pub fn new(order_id:i64) -> Self {
+        Self {
+            order_id,
+            is_automated: true,
+            ..Default::default()
+        }
+    }
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+/// The payload of the order/liquidateposition endpoint, it cancels the working orders of the
+/// contract and flattens the position.
+pub struct LiquidatePosition {
+    pub account_id: i64,
+    pub contract_id: i64,
+    pub admin: bool,
+    pub custom_tag50: Option<String>,
+}
+impl LiquidatePosition {
+    pub fn new(account_id:i64,contract_id:i64) -> Self {
+        Self {
+            account_id,
+            contract_id,
+            ..Default::default()
+        }
+    }
+}
+
+/// The response to an order command. The server answers a refused command with a
+/// `failure_reason` instead of an error status, over REST and the socket alike.
+pub trait CommandResponse: Sized {
+    fn failure_reason(&self) -> Option<&str>;
+    fn failure_text(&self) -> Option<&str>;
+    /// Turns a refused command into `Error::OrderRejected`.
+    fn check(self) -> Result<Self, Error> {
+        match self.failure_reason() {
+            Some(reason) => Err(Error::OrderRejected {
+                reason: reason.to_string(),
+                text: self.failure_text().map(str::to_string),
+            }),
+            None => Ok(self),
+        }
+    }
+}
+
+macro_rules! impl_command_response {
+    ($($result:ty),*) => {
+        $(impl CommandResponse for $result {
+            fn failure_reason(&self) -> Option<&str> {
+                self.failure_reason.as_deref()
+            }
+            fn failure_text(&self) -> Option<&str> {
+                self.failure_text.as_deref()
+            }
+        })*
+    };
+}
+impl_command_response!(CommandResult, PlaceOrderResult, OsoResult, OcoResult);
+
+#[serde_with::skip_serializing_none]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+/// The response to a command on an existing order, a `failure_reason` means it was refused.
+pub struct CommandResult {
+    pub command_id: Option<i64>,
+    pub failure_reason: Option<String>,
+    pub failure_text: Option<String>,
+}
+impl CommandResult {
+    pub fn is_ok(&self) -> bool {
+        self.failure_reason.is_none()
+    }
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+/// The response to a command that places an order, a `failure_reason` means it was refused.
+pub struct PlaceOrderResult {
+    pub order_id: Option<i64>,
+    pub failure_reason: Option<String>,
+    pub failure_text: Option<String>,
+}
+impl PlaceOrderResult {
+    pub fn is_ok(&self) -> bool {
+        self.failure_reason.is_none()
+    }
+}
+
+
+/// Why an order was refused before being sent.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderValidationError {
+    /// The action is still the default `OrderAction::Dont`.
+    MissingAction,
+    MissingSymbol,
+    InvalidQuantity(i64),
+    /// The order type needs a price that was not set.
+    MissingPrice { order_type: OrderType },
+    /// The order type needs a stop price that was not set.
+    MissingStopPrice { order_type: OrderType },
+    /// `TimeInForce::Gtd` needs an expire time.
+    MissingExpireTime,
+    /// An OSO needs at least one bracket.
+    MissingBracket,
+    /// Prices can only be rounded to a tick size above zero, the product's is this.
+    InvalidTickSize(Decimal),
+    /// The brackets of an OSO with a market entry are checked against its `reference_price`, which was not set.
+    MissingReferencePrice,
+    /// Brackets must close the entry, so their action must be the opposite one.
+    BracketAction { entry: OrderAction, bracket: OrderAction },
+    /// A target at or beyond the stop side of `reference`, or a stop at or beyond its target side.
+    WrongSide { order_type: OrderType, price: Decimal, reference: Decimal },
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+/// An order linked to another one, a bracket of an OSO or the other side of an OCO.
+pub struct BracketLeg {
+    pub action: OrderAction,
+    pub cl_ord_id: Option<String>,
+    pub order_type: OrderType,
+    pub price: Option<Decimal>,
+    pub stop_price: Option<Decimal>,
+    pub max_show: Option<Decimal>,
+    pub peg_difference: Option<Decimal>,
+    pub time_in_force: Option<TimeInForce>,
+    pub expire_time: Option<String>,
+    pub text: Option<String>,
+}
+impl BracketLeg {
+    pub fn limit(action:OrderAction,price:Decimal) -> Self {
+        Self {
+            action,
+            order_type: OrderType::Limit,
+            price: Some(price),
+            ..Default::default()
+        }
+    }
+    pub fn stop(action:OrderAction,stop_price:Decimal) -> Self {
+        Self {
+            action,
+            order_type: OrderType::Stop,
+            stop_price: Some(stop_price),
+            ..Default::default()
+        }
+    }
+    pub fn validate(&self) -> Result<(), OrderValidationError> {
+        check_order_fields(self.order_type, self.price, self.stop_price, self.time_in_force, &self.expire_time)
+    }
+    /// The price that makes the leg trade, `None` for market orders.
+    fn trigger_price(&self) -> Result<Option<Decimal>, OrderValidationError> {
+        order_trigger_price(self.order_type, self.price, self.stop_price)
+    }
+    /// Checks that the leg closes at a profit if it is a target and at a loss if it is a stop,
+    /// relative to `reference`. See `OrderType::is_target` and `OrderType::is_stop`.
+    fn check_side(&self, reference: Decimal) -> Result<(), OrderValidationError> {
+        let Some(price) = self.trigger_price()? else {
+            return Ok(());
+        };
+        let is_target = if self.order_type.is_target() {
+            true
+        } else if self.order_type.is_stop() {
+            false
+        } else {
+            return Ok(());
+        };
+        let above = price > reference;
+        let below = price < reference;
+        let valid = match (&self.action, is_target) {
+            (OrderAction::Sell, true) | (OrderAction::Buy, false) => above,
+            (OrderAction::Buy, true) | (OrderAction::Sell, false) => below,
+            (OrderAction::Dont, _) => true,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(OrderValidationError::WrongSide {
+                order_type: self.order_type,
+                price,
+                reference,
+            })
+        }
+    }
+}
+
+fn check_order_fields(order_type:OrderType,price:Option<Decimal>,stop_price:Option<Decimal>,time_in_force:Option<TimeInForce>,expire_time:&Option<String>) -> Result<(), OrderValidationError> {
+    if order_type.needs_price() && price.is_none() {
+        return Err(OrderValidationError::MissingPrice { order_type });
+    }
+    if order_type.needs_stop_price() && stop_price.is_none() {
+        return Err(OrderValidationError::MissingStopPrice { order_type });
+    }
+    if time_in_force == Some(TimeInForce::Gtd) && expire_time.is_none() {
+        return Err(OrderValidationError::MissingExpireTime);
+    }
+    Ok(())
+}
+
+/// The price that makes an order trade, `None` for orders without one.
+fn order_trigger_price(order_type:OrderType,price:Option<Decimal>,stop_price:Option<Decimal>) -> Result<Option<Decimal>, OrderValidationError> {
+    check_order_fields(order_type, price, stop_price, None, &None)?;
+    if order_type.needs_stop_price() {
+        Ok(stop_price)
+    } else if order_type == OrderType::Limit {
+        Ok(price)
+    } else {
+        Ok(None)
+    }
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// The payload of the order/placeOSO endpoint: an entry order whose brackets are only
+/// placed once it fills.
+pub struct OsoOrder {
+    #[serde(flatten)]
+    pub entry: OrderTicket,
+    pub bracket1: Option<BracketLeg>,
+    pub bracket2: Option<BracketLeg>,
+    /// Used to validate the brackets of a market entry, usually the last price.
+    #[serde(skip)]
+    pub reference_price: Option<Decimal>,
+}
+impl OsoOrder {
+    pub fn new(entry:OrderTicket) -> Self {
+        Self {
+            entry,
+            ..Default::default()
+        }
+    }
+    /// Adds a limit order closing the entry at `price`.
+    pub fn take_profit(self, price:Decimal) -> Self {
+        let action = self.entry.action.opposite();
+        self.bracket(BracketLeg::limit(action, price))
+    }
+    /// Adds a stop order closing the entry at `stop_price`.
+    pub fn stop_loss(self, stop_price:Decimal) -> Self {
+        let action = self.entry.action.opposite();
+        self.bracket(BracketLeg::stop(action, stop_price))
+    }
+    /// Fills the first free bracket, an OSO has at most two so a third one replaces the second.
+    pub fn bracket(mut self, leg:BracketLeg) -> Self {
+        if self.bracket1.is_none() {
+            self.bracket1 = Some(leg);
+        } else {
+            self.bracket2 = Some(leg);
+        }
+        self
+    }
+    pub fn reference_price(mut self, price:Decimal) -> Self {
+        self.reference_price = Some(price);
+        self
+    }
+    /// Checks that every bracket closes the entry on the right side of its price,
+    /// or of `reference_price` for market entries, which must then be set.
+    pub fn validate(&self) -> Result<(), OrderValidationError> {
+        self.entry.validate()?;
+        if self.bracket1.is_none() {
+            return Err(OrderValidationError::MissingBracket);
+        }
+        let entry_price = order_trigger_price(self.entry.order_type, self.entry.price, self.entry.stop_price)?
+            .or(self.reference_price)
+            .ok_or(OrderValidationError::MissingReferencePrice)?;
+        for leg in self.bracket1.iter().chain(self.bracket2.iter()) {
+            if leg.action != self.entry.action.opposite() {
+                return Err(OrderValidationError::BracketAction {
+                    entry: self.entry.action.clone(),
+                    bracket: leg.action.clone(),
+                });
+            }
+            leg.validate()?;
+            leg.check_side(entry_price)?;
+        }
+        Ok(())
+    }
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// The payload of the order/placeOCO endpoint: two orders where the fill of one cancels the other.
+pub struct OcoOrder {
+    #[serde(flatten)]
+    pub order: OrderTicket,
+    pub other: BracketLeg,
+}
+impl OcoOrder {
+    pub fn new(order:OrderTicket,other:BracketLeg) -> Self {
+        Self { order, other }
+    }
+    /// A target and a stop closing a position with `action`, the usual bracket of an open position.
+    pub fn exit(username:&str,account_id:i64,symbol:&str,action:OrderAction,qty:i64,target:Decimal,stop_price:Decimal) -> Self {
+        let order = OrderTicket {
+            account_spec: username.to_string(),
+            account_id,
+            action: action.clone(),
+            symbol: symbol.to_string(),
+            order_qty: qty,
+            order_type: OrderType::Limit,
+            price: Some(target),
             is_automated: true,
             ..Default::default()
+        };
+        Self::new(order, BracketLeg::stop(action, stop_price))
+    }
+    /// When both orders have the same action and one is a target and the other a stop,
+    /// checks that the target is on the profit side of the stop.
+    pub fn validate(&self) -> Result<(), OrderValidationError> {
+        self.order.validate()?;
+        self.other.validate()?;
+        let price = order_trigger_price(self.order.order_type, self.order.price, self.order.stop_price)?;
+        let other_price = self.other.trigger_price()?;
+        let order_type = self.order.order_type;
+        let other_type = self.other.order_type;
+        let target_and_stop = (order_type.is_target() && other_type.is_stop()) || (order_type.is_stop() && other_type.is_target());
+        if let (Some(price), Some(_)) = (price, other_price) {
+            if self.order.action == self.other.action && target_and_stop {
+                self.other.check_side(price)?;
+            }
         }
+        Ok(())
+    }
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+/// The ids of the entry and of each bracket of a placed OSO.
+pub struct OsoResult {
+    pub order_id: Option<i64>,
+    pub oso1_id: Option<i64>,
+    pub oso2_id: Option<i64>,
+    pub failure_reason: Option<String>,
+    pub failure_text: Option<String>,
+}
+impl OsoResult {
+    pub fn is_ok(&self) -> bool {
+        self.failure_reason.is_none()
     }
-}
\ No newline at end of file
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+/// The ids of both orders of a placed OCO.
+pub struct OcoResult {
+    pub order_id: Option<i64>,
+    pub oco_id: Option<i64>,
+    pub failure_reason: Option<String>,
+    pub failure_text: Option<String>,
+}
+impl OcoResult {
+    pub fn is_ok(&self) -> bool {
+        self.failure_reason.is_none()
+    }
+}
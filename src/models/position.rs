@@ -47,7 +47,7 @@ where
     let s: String = Deserialize::deserialize(deserializer)?;
     let dt = chrono::NaiveDateTime::parse_from_str(&s, "%Y-%m-%dT%H:%M:%S%.fZ")
         .map_err(de::Error::custom)?;
-    Ok(chrono::DateTime::<Utc>::from_utc(dt, Utc))
+    Ok(chrono::DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc))
 }
 
 
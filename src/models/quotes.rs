@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use chrono::DateTime;
@@ -30,28 +31,86 @@ pub struct Quote {
     #[serde(deserialize_with = "parse_timestamp")]
     pub timestamp: DateTime<chrono::Utc>,
 }
+impl Quote {
+    pub fn merge(&mut self, update: Quote) {
+        self.id = update.id;
+        self.timestamp = update.timestamp;
+        self.entries.merge(update.entries);
+    }
+    pub fn spread(&self) -> Option<Decimal> {
+        Some(self.entries.offer.as_ref()?.price - self.entries.bid.as_ref()?.price)
+    }
+}
+
+/// The last known quote of every contract, built by merging the partial updates the server sends.
+#[derive(Debug, Clone, Default)]
+pub struct QuoteCache {
+    quotes: Arc<std::sync::RwLock<HashMap<i64, Quote>>>,
+}
+impl QuoteCache {
+    /// Merges the update into the contract's last quote and returns the result.
+    pub fn update(&self, quote: Quote) -> Quote {
+        let mut quotes = self.quotes.write().unwrap();
+        match quotes.get_mut(&quote.contract_id) {
+            Some(last) => {
+                last.merge(quote);
+                last.clone()
+            }
+            None => {
+                quotes.insert(quote.contract_id, quote.clone());
+                quote
+            }
+        }
+    }
+    pub fn get(&self, contract_id: i64) -> Option<Quote> {
+        self.quotes.read().unwrap().get(&contract_id).cloned()
+    }
+    pub fn all(&self) -> Vec<Quote> {
+        self.quotes.read().unwrap().values().cloned().collect()
+    }
+}
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(default)]
 pub struct Entries {
     #[serde(rename = "Bid")]
-    pub bid: PriceSize,
+    pub bid: Option<PriceSize>,
     #[serde(rename = "HighPrice")]
-    pub high_price: HighPrice,
+    pub high_price: Option<HighPrice>,
     #[serde(rename = "LowPrice")]
-    pub low_price: LowPrice,
+    pub low_price: Option<LowPrice>,
     #[serde(rename = "Offer")]
-    pub offer: PriceSize,
+    pub offer: Option<PriceSize>,
     #[serde(rename = "OpenInterest")]
-    pub open_interest: OpenInterest,
+    pub open_interest: Option<OpenInterest>,
     #[serde(rename = "OpeningPrice")]
-    pub opening_price: OpeningPrice,
+    pub opening_price: Option<OpeningPrice>,
     #[serde(rename = "SettlementPrice")]
-    pub settlement_price: SettlementPrice,
+    pub settlement_price: Option<SettlementPrice>,
     #[serde(rename = "TotalTradeVolume")]
-    pub total_trade_volume: TotalTradeVolume,
+    pub total_trade_volume: Option<TotalTradeVolume>,
     #[serde(rename = "Trade")]
-    pub trade: PriceSize
+    pub trade: Option<PriceSize>
+}
+impl Entries {
+    /// Tradovate only sends the entries that changed, so an update overwrites the ones it carries
+    /// and keeps the rest.
+    pub fn merge(&mut self, update: Entries) {
+        fn keep<T>(current: &mut Option<T>, update: Option<T>) {
+            if update.is_some() {
+                *current = update;
+            }
+        }
+        keep(&mut self.bid, update.bid);
+        keep(&mut self.high_price, update.high_price);
+        keep(&mut self.low_price, update.low_price);
+        keep(&mut self.offer, update.offer);
+        keep(&mut self.open_interest, update.open_interest);
+        keep(&mut self.opening_price, update.opening_price);
+        keep(&mut self.settlement_price, update.settlement_price);
+        keep(&mut self.total_trade_volume, update.total_trade_volume);
+        keep(&mut self.trade, update.trade);
+    }
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+/// The reason codes Tradovate sends in a `shutdown` event before closing a socket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ShutdownReasonCode {
+    Maintenance,
+    ConnectionQuotaReached,
+    IPQuotaReached,
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+impl ShutdownReasonCode {
+    /// Maintenance ends and the server accepts connections again, but reconnecting
+    /// when a quota has been reached only uses up more of it.
+    pub fn should_reconnect(&self) -> bool {
+        !matches!(
+            self,
+            ShutdownReasonCode::ConnectionQuotaReached | ShutdownReasonCode::IPQuotaReached
+        )
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct ShutdownMessage {
+    pub reason_code: ShutdownReasonCode,
+    pub reason: Option<String>,
+}
@@ -5,7 +5,7 @@ use rayon::slice::ParallelSliceMut;
 use rust_decimal::prelude::ToPrimitive;
 use serde::Serialize;
 use serde::Deserialize;
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
 use rust_decimal::Decimal;
 use super::time_and_sales::OrderAction;
 use super::time_and_sales::TimeAndSalesItem;
@@ -26,6 +26,18 @@ impl ChartData {
             .collect_into_vec(&mut vec_of_vec);
         vec_of_vec.into_iter().flatten().collect()
     }
+    /// The bars of every chart in the packet, tagged with the id of the chart they belong to.
+    pub fn get_all_bars(&self) -> Vec<Bar> {
+        self.charts
+            .iter()
+            .flat_map(|chart| {
+                chart.bars.iter().map(|bar| Bar {
+                    historical_id: chart.historical_id,
+                    ..bar.clone()
+                })
+            })
+            .collect()
+    }
     pub fn combine_all_ticks(&self) -> Option<ChartSummary>  {
         let mut items = self.get_all_ts_items();
         items.par_sort_unstable_by_key(|i| i.timestamp);
@@ -115,6 +127,8 @@ pub struct Chart {
     pub ticks: Vec<Tick>,
     #[serde(rename = "ts")]
     pub tick_size: Decimal,
+    /// Sent instead of `tks` by every chart description other than the tick stream.
+    pub bars: Vec<Bar>,
     pub eoh: bool,
 }
 impl Chart {
@@ -179,6 +193,7 @@ impl Tick {
         };
         TimeAndSalesItem {
             historical_id,
+            tick_id: self.tick_id,
             action,
             qty: self.tick_volume,
             price,
@@ -191,6 +206,46 @@ impl Tick {
     }
 }
 
+/// One element of a bar chart: minute, daily, volume, range or renko bars.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct Bar {
+    /// The id of the chart packet the bar came in, not sent by the server inside the bar.
+    #[serde(skip_deserializing)]
+    pub historical_id: i64,
+    #[serde(deserialize_with = "parse_bar_timestamp")]
+    pub timestamp: DateTime<Utc>,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub up_volume: Decimal,
+    pub down_volume: Decimal,
+    pub up_ticks: Decimal,
+    pub down_ticks: Decimal,
+    pub bid_volume: Decimal,
+    pub offer_volume: Decimal,
+}
+impl Bar {
+    pub fn volume(&self) -> Decimal {
+        self.up_volume + self.down_volume
+    }
+    /// Volume traded at the offer minus volume traded at the bid.
+    pub fn delta(&self) -> Decimal {
+        self.offer_volume - self.bid_volume
+    }
+}
+
+fn parse_bar_timestamp<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s: String = Deserialize::deserialize(deserializer)?;
+    let dt = DateTime::parse_from_rfc3339(&s).map_err(serde::de::Error::custom)?;
+    Ok(dt.with_timezone(&Utc))
+}
+
 fn parse_trade_date<'de, D>(deserializer: D) -> Result<NaiveDate, D::Error>
 where
     D: serde::Deserializer<'de>,
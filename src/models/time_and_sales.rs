@@ -5,7 +5,9 @@ use serde::Deserialize;
 use serde::Serialize;
 use tokio::sync::RwLock;
 
-use super::tick_chart::ChartSummary;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::tick_chart::{ChartData, ChartSummary};
 pub type TimeAndSalesRWL = Arc<RwLock<Vec<ChartSummary>>>;
 
 
@@ -28,6 +30,7 @@ impl OrderAction {
 #[serde(default)]
 pub struct TimeAndSalesItem {
     pub historical_id: i64,
+    pub tick_id: i64,
     pub action: OrderAction,
     pub qty: i64,
     pub price: Decimal,
@@ -46,6 +49,78 @@ impl TimeAndSalesItem {
         }
     }
 }
+/// What the tick stream of a chart subscription delivers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TickEvent {
+    Trade(TimeAndSalesItem),
+    /// The backfill of the chart with this id is over, every following trade is live.
+    EndOfHistory(i64),
+}
+
+/// How many tick ids of each chart are remembered to drop resent ticks.
+pub const SEEN_TICKS_WINDOW: usize = 10_000;
+
+/// The last `SEEN_TICKS_WINDOW` tick ids of a chart, oldest first.
+#[derive(Debug, Default, Clone)]
+struct SeenTicks {
+    ids: HashSet<i64>,
+    order: VecDeque<i64>,
+}
+impl SeenTicks {
+    /// False if the id was already seen.
+    fn insert(&mut self, tick_id: i64) -> bool {
+        if !self.ids.insert(tick_id) {
+            return false;
+        }
+        self.order.push_back(tick_id);
+        if self.order.len() > SEEN_TICKS_WINDOW {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+/// Turns chart packets into a clean tape where every trade is delivered once, even when the
+/// server resends ticks in overlapping packets.
+/// The trades of a packet are delivered in timestamp order, packets in the order they arrive,
+/// so a tick arriving late comes after newer ticks that were already delivered.
+/// Only the last `SEEN_TICKS_WINDOW` ticks of a chart are remembered, a tick resent after that is delivered again.
+#[derive(Debug, Default, Clone)]
+pub struct TickTape {
+    seen: HashMap<i64, SeenTicks>,
+    live: HashSet<i64>,
+}
+impl TickTape {
+    pub fn process(&mut self, chart_data: &ChartData) -> Vec<TickEvent> {
+        let mut events = Vec::new();
+        for chart in &chart_data.charts {
+            let mut items = chart.get_ts_items();
+            items.sort_by_key(|item| (item.timestamp, item.tick_id));
+            let seen = self.seen.entry(chart.historical_id).or_default();
+            for item in items {
+                if seen.insert(item.tick_id) {
+                    events.push(TickEvent::Trade(item));
+                }
+            }
+            if chart.eoh && self.live.insert(chart.historical_id) {
+                events.push(TickEvent::EndOfHistory(chart.historical_id));
+            }
+        }
+        events
+    }
+    /// True once the chart's end of history has been received.
+    pub fn is_live(&self, historical_id: i64) -> bool {
+        self.live.contains(&historical_id)
+    }
+    /// Forgets a cancelled chart.
+    pub fn clear(&mut self, historical_id: i64) {
+        self.seen.remove(&historical_id);
+        self.live.remove(&historical_id);
+    }
+}
+
 pub fn new_time_and_sales_rwl() -> Arc<RwLock<Vec<ChartSummary>>> {
     Arc::new(RwLock::new(Vec::new()))
 }
\ No newline at end of file
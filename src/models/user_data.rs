@@ -3,6 +3,10 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use super::execution::{Command, CommandReport, ExecutionReport, Fill, FillPair, Order, OrderVersion};
+use super::order_strategy::{OrderStrategy, OrderStrategyLink};
+use super::position::Position;
+
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -25,21 +29,21 @@ pub struct UserData {
     pub user_account_auto_liqs: Vec<UserAccountAutoLiq>,
     pub cash_balances: Vec<CashBalance>,
     pub currencies: Vec<Currency>,
-    pub positions: Vec<Value>,
-    pub fill_pairs: Vec<Value>,
-    pub orders: Vec<Value>,
+    pub positions: Vec<Position>,
+    pub fill_pairs: Vec<FillPair>,
+    pub orders: Vec<Order>,
     pub contracts: Vec<Value>,
     pub contract_maturities: Vec<Value>,
     pub products: Vec<Value>,
     pub exchanges: Vec<Exchange>,
     pub spread_definitions: Vec<Value>,
-    pub commands: Vec<Value>,
-    pub command_reports: Vec<Value>,
-    pub execution_reports: Vec<Value>,
-    pub order_versions: Vec<Value>,
-    pub fills: Vec<Value>,
-    pub order_strategies: Vec<Value>,
-    pub order_strategy_links: Vec<Value>,
+    pub commands: Vec<Command>,
+    pub command_reports: Vec<CommandReport>,
+    pub execution_reports: Vec<ExecutionReport>,
+    pub order_versions: Vec<OrderVersion>,
+    pub fills: Vec<Fill>,
+    pub order_strategies: Vec<OrderStrategy>,
+    pub order_strategy_links: Vec<OrderStrategyLink>,
     pub user_properties: Vec<UserProperty>,
     pub properties: Vec<Value>,
     pub user_plugins: Vec<UserPlugin>,
@@ -81,8 +85,13 @@ pub struct Account {
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[serde(default)]
 pub struct AccountRiskStatuse {
     pub id: i64,
+    pub admin_action: Option<String>,
+    pub admin_timestamp: Option<String>,
+    pub liquidate_only: Option<String>,
+    pub user_triggered_liq_only: Option<bool>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -4,6 +4,30 @@ pub struct Endpoint {
     pub path: &'static str,
     pub method: Method,
 }
+impl Endpoint {
+    pub fn class(&self) -> EndpointClass {
+        if self.path.starts_with("/v1/auth/") {
+            EndpointClass::Auth
+        } else if self.method == Method::POST
+            && (self.path.starts_with("/v1/order/") || self.path.starts_with("/v1/orderStrategy/"))
+        {
+            EndpointClass::Order
+        } else {
+            EndpointClass::Query
+        }
+    }
+}
+
+/// Endpoints of a class share a rate limit, see `RateLimits`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndpointClass {
+    /// Token requests and renewals.
+    Auth,
+    /// Commands that place, change or cancel orders.
+    Order,
+    /// Everything that only reads.
+    Query,
+}
 
 pub const ACCESS_TOKEN: Endpoint = Endpoint {
     path: "/v1/auth/accesstokenrequest",
@@ -58,4 +82,84 @@ pub const ACCOUNTS_LIST: Endpoint = Endpoint {
 pub const CASH_BALANCE_LIST: Endpoint = Endpoint {
     path: "/v1/cashBalance/list",
     method: Method::GET,
-};
\ No newline at end of file
+};
+
+pub const MODIFY_ORDER: Endpoint = Endpoint {
+    path: "/v1/order/modifyorder",
+    method: Method::POST,
+};
+
+pub const CANCEL_ORDER: Endpoint = Endpoint {
+    path: "/v1/order/cancelorder",
+    method: Method::POST,
+};
+
+pub const LIQUIDATE_POSITION: Endpoint = Endpoint {
+    path: "/v1/order/liquidateposition",
+    method: Method::POST,
+};
+
+pub const PLACE_OSO: Endpoint = Endpoint {
+    path: "/v1/order/placeOSO",
+    method: Method::POST,
+};
+
+pub const PLACE_OCO: Endpoint = Endpoint {
+    path: "/v1/order/placeOCO",
+    method: Method::POST,
+};
+
+pub const START_ORDER_STRATEGY: Endpoint = Endpoint {
+    path: "/v1/orderStrategy/startOrderStrategy",
+    method: Method::POST,
+};
+
+pub const MODIFY_ORDER_STRATEGY: Endpoint = Endpoint {
+    path: "/v1/orderStrategy/modifyOrderStrategy",
+    method: Method::POST,
+};
+
+pub const INTERRUPT_ORDER_STRATEGY: Endpoint = Endpoint {
+    path: "/v1/orderStrategy/interruptOrderStrategy",
+    method: Method::POST,
+};
+
+pub const ORDER_LIST: Endpoint = Endpoint {
+    path: "/v1/order/list",
+    method: Method::GET,
+};
+
+pub const ORDER_ITEM: Endpoint = Endpoint {
+    path: "/v1/order/item",
+    method: Method::GET,
+};
+
+pub const ORDER_VERSION_DEPS: Endpoint = Endpoint {
+    path: "/v1/orderVersion/deps",
+    method: Method::GET,
+};
+
+pub const FILL_LIST: Endpoint = Endpoint {
+    path: "/v1/fill/list",
+    method: Method::GET,
+};
+
+pub const FILL_DEPS: Endpoint = Endpoint {
+    path: "/v1/fill/deps",
+    method: Method::GET,
+};
+
+pub const EXECUTION_REPORT_DEPS: Endpoint = Endpoint {
+    path: "/v1/executionReport/deps",
+    method: Method::GET,
+};
+
+pub const COMMAND_DEPS: Endpoint = Endpoint {
+    path: "/v1/command/deps",
+    method: Method::GET,
+};
+
+pub const COMMAND_REPORT_DEPS: Endpoint = Endpoint {
+    path: "/v1/commandReport/deps",
+    method: Method::GET,
+};
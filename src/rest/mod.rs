@@ -1 +1,3 @@
-pub mod endpoints;
\ No newline at end of file
+pub mod endpoints;
+pub mod rate_limit;
+pub mod retry;
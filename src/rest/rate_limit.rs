@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use log::debug;
+
+use super::endpoints::{Endpoint, EndpointClass};
+
+/// A bucket holding up to `capacity` requests, refilled at `per_second` requests a second.
+/// A capacity below one request or a rate that is not a positive finite number means no limit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BucketLimit {
+    pub capacity: f64,
+    pub per_second: f64,
+}
+impl BucketLimit {
+    /// `requests` every `period`, all of them usable in a single burst.
+    pub fn new(requests: u32, period: Duration) -> Self {
+        Self {
+            capacity: f64::from(requests),
+            per_second: f64::from(requests) / period.as_secs_f64(),
+        }
+    }
+    pub fn is_unlimited(&self) -> bool {
+        self.capacity < 1.0 || !(self.per_second.is_finite() && self.per_second > 0.0)
+    }
+}
+
+/// The client side limit of each endpoint class, kept below the limits Tradovate enforces
+/// so requests are spread out instead of drawing penalty tickets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimits {
+    pub auth: BucketLimit,
+    pub order: BucketLimit,
+    pub query: BucketLimit,
+}
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            auth: BucketLimit::new(2, Duration::from_secs(10)),
+            order: BucketLimit::new(10, Duration::from_secs(2)),
+            query: BucketLimit::new(60, Duration::from_secs(60)),
+        }
+    }
+}
+impl RateLimits {
+    pub fn limit(&self, class: EndpointClass) -> BucketLimit {
+        match class {
+            EndpointClass::Auth => self.auth,
+            EndpointClass::Order => self.order,
+            EndpointClass::Query => self.query,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    limit: BucketLimit,
+    tokens: f64,
+    last_refill: Instant,
+}
+impl TokenBucket {
+    /// A full bucket.
+    pub fn new(limit: BucketLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            tokens: limit.capacity,
+            last_refill: now,
+        }
+    }
+    /// Takes a token if there is one, otherwise returns how long until the next one is available.
+    pub fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        if self.limit.is_unlimited() {
+            return Ok(());
+        }
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.per_second).min(self.limit.capacity);
+        self.last_refill = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / self.limit.per_second))
+        }
+    }
+}
+
+/// Shared between every clone of the client, so all of them draw from the same buckets.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    limits: RateLimits,
+    buckets: Arc<Mutex<HashMap<EndpointClass, TokenBucket>>>,
+}
+impl RateLimiter {
+    pub fn new(limits: RateLimits) -> Self {
+        Self {
+            limits,
+            buckets: Arc::default(),
+        }
+    }
+    pub fn limits(&self) -> RateLimits {
+        self.limits
+    }
+    /// Takes a token from the endpoint's bucket without waiting.
+    pub fn try_acquire(&self, endpoint: &Endpoint) -> Result<(), Duration> {
+        let class = endpoint.class();
+        let now = Instant::now();
+        self.buckets
+            .lock()
+            .unwrap()
+            .entry(class)
+            .or_insert_with(|| TokenBucket::new(self.limits.limit(class), now))
+            .try_acquire(now)
+    }
+    /// Waits until the endpoint's bucket has a token and takes it.
+    pub async fn acquire(&self, endpoint: &Endpoint) {
+        while let Err(wait) = self.try_acquire(endpoint) {
+            debug!("Rate limiting {}, waiting {:?}", endpoint.path, wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
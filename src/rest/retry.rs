@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use rand::Rng;
+use reqwest::Method;
+use serde_json::Value;
+
+use super::endpoints::{Endpoint, PLACE_ORDER};
+use crate::error::Error;
+
+/// How failed REST calls are retried.
+/// Only requests that are safe to send twice are retried: every GET, and `PLACE_ORDER`
+/// when the order carries a `clOrdId` the server can deduplicate it by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt, zero disables retrying.
+    pub max_retries: u32,
+    /// The delay before the first retry, doubled on every following one.
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+    pub fn new(max_retries: u32, base_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            max_retries,
+            base_delay,
+            max_delay,
+        }
+    }
+    /// The exponential delay before retry number `attempt`, starting at zero, without jitter.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay)
+    }
+    /// A random delay between half and all of `backoff`, so clients failing together don't retry together.
+    pub fn jittered_backoff(&self, attempt: u32) -> Duration {
+        let backoff = self.backoff(attempt);
+        rand::thread_rng().gen_range(backoff / 2..=backoff)
+    }
+    /// Whether a failed attempt, `attempt` retries in, should be tried again.
+    pub fn should_retry(&self, attempt: u32, endpoint: &Endpoint, request_body: Option<&Value>, error: &Error) -> bool {
+        attempt < self.max_retries && is_idempotent(endpoint, request_body) && error.is_transient()
+    }
+}
+
+/// True if sending the request twice has the same effect as sending it once.
+pub fn is_idempotent(endpoint: &Endpoint, request_body: Option<&Value>) -> bool {
+    if endpoint.method == Method::GET {
+        return true;
+    }
+    endpoint.path == PLACE_ORDER.path
+        && request_body
+            .and_then(|body| body["clOrdId"].as_str())
+            .is_some_and(|cl_ord_id| !cl_ord_id.is_empty())
+}
+
+/// The timeout and retry policy of REST calls.
+/// Set for every call with `TradovateClientBuilder`, and overridden for some of them
+/// with `TradovateClient::with_retry_policy` and `TradovateClient::with_timeout`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestOptions {
+    /// The longest a whole attempt, from sending to reading the body, can take.
+    pub timeout: Duration,
+    pub retry_policy: RetryPolicy,
+}
+impl Default for RequestOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+}
@@ -1,3 +1,22 @@
-
 pub mod test_client;
-//pub mod test_websocket;
\ No newline at end of file
+//pub mod test_websocket;
+pub mod test_rpc;
+pub mod test_frames;
+pub mod test_reconnect;
+pub mod test_subscriptions;
+pub mod test_feeds;
+pub mod test_quotes;
+pub mod test_histogram;
+pub mod test_charts;
+pub mod test_orderbook;
+pub mod test_orders;
+pub mod test_order_strategy;
+pub mod test_execution;
+pub mod test_account_events;
+pub mod test_order_gateway;
+pub mod test_error;
+pub mod test_rate_limit;
+pub mod test_retry;
+pub mod test_client_builder;
+pub mod test_token_store;
+pub mod test_access_token;
@@ -0,0 +1,163 @@
+use futures::{SinkExt, StreamExt};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::client::{ResourceType, Server, TradovateClient};
+use crate::error::Error;
+use crate::models::access_token::AccessTokenInfo;
+use crate::tests::test_client_builder::{credentials, serve_http_with_headers};
+use crate::token_store::NoTokenStore;
+
+fn token(access_token: &str, has_live: bool, has_funded: bool) -> AccessTokenInfo {
+    AccessTokenInfo {
+        access_token: access_token.to_string(),
+        md_access_token: "md".to_string(),
+        name: "user".to_string(),
+        has_live,
+        has_funded,
+        has_market_data: true,
+        ..Default::default()
+    }
+}
+
+fn client(server_type: Server, base_url: &str) -> TradovateClient {
+    TradovateClient::builder(server_type)
+        .credentials(credentials())
+        .base_url(base_url)
+        .token_store(NoTokenStore)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_socket_tokens() {
+    let both = token("trading", false, false);
+    assert_eq!(both.ws_auth_msg(ResourceType::Trading, 1), "authorize\n1\n\ntrading");
+    assert_eq!(both.ws_auth_msg(ResourceType::MarketData, 2), "authorize\n2\n\nmd");
+    assert_eq!(both.ws_auth_msg(ResourceType::MarketReplay, 3), "authorize\n3\n\nmd");
+    let trading_only = AccessTokenInfo {
+        md_access_token: String::new(),
+        ..both.clone()
+    };
+    assert_eq!(trading_only.token_for(ResourceType::MarketData), "trading");
+}
+
+#[tokio::test]
+async fn test_trading_permissions() {
+    assert!(token("trading", false, false).check_trading(Server::Demo).is_ok());
+    assert!(matches!(
+        token("trading", false, true).check_trading(Server::Live),
+        Err(Error::NotPermitted(_))
+    ));
+    assert!(matches!(
+        token("trading", true, false).check_trading(Server::Live),
+        Err(Error::NotPermitted(_))
+    ));
+    assert!(token("trading", true, true).check_trading(Server::Live).is_ok());
+    let md_only = token("", true, true);
+    assert!(!md_only.has_trading());
+    assert!(matches!(md_only.check_trading(Server::Demo), Err(Error::NotPermitted(_))));
+
+    let client = client(Server::Live, "http://127.0.0.1:1");
+    assert!(matches!(client.check_trading(), Err(Error::Auth(_))));
+    client.token.set(md_only);
+    assert!(client.has_live() && client.has_funded() && client.has_market_data());
+    assert_eq!(client.token.access_token(), None);
+    assert!(matches!(
+        client.open_socket(ResourceType::Trading).await,
+        Err(Error::NotPermitted(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_market_data_socket_uses_md_token() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        let frame = match ws.next().await {
+            Some(Ok(Message::Text(frame))) => frame,
+            other => panic!("expected the authorize frame, got {:?}", other),
+        };
+        ws.send(Message::Text(r#"a[{"s":200,"i":1}]"#.to_string())).await.unwrap();
+        frame
+    });
+    let client = client(Server::Demo, &format!("http://{}", address));
+    client.token.set(token("", false, false));
+    let _socket = client.open_socket(ResourceType::MarketData).await.unwrap();
+    assert_eq!(server.await.unwrap(), "authorize\n1\n\nmd");
+}
+
+#[tokio::test]
+async fn test_user_sync_needs_a_token() {
+    let client = client(Server::Demo, "http://127.0.0.1:9");
+    assert!(matches!(client.user_sync_request_body(), Err(Error::Auth(_))));
+    client.token.set(AccessTokenInfo {
+        user_id: 3,
+        ..token("trading", false, false)
+    });
+    assert_eq!(client.get_user_sync_request(4).unwrap(), "user/syncrequest\n4\n\n{\"users\":[3]}");
+}
+
+#[tokio::test]
+async fn test_renewed_tokens_use_new_request_ids() {
+    use std::sync::Arc;
+    use tokio::sync::Mutex;
+    use crate::token_manager::TokenHandle;
+    use crate::websocket::connection::forward_token_renewals;
+    use crate::websocket::rpc::{PendingRequests, WsRpc};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        let mut frames = Vec::new();
+        for _ in 0..2 {
+            let Some(Ok(Message::Text(frame))) = ws.next().await else {
+                break;
+            };
+            frames.push(frame);
+        }
+        ws.close(None).await.unwrap();
+        frames
+    });
+    let (ws, _) = tokio_tungstenite::connect_async(format!("ws://{}", address)).await.unwrap();
+    let (writer, _reader) = ws.split();
+    let pending = Arc::new(PendingRequests::new());
+    // the id the socket was first authorized with
+    assert_eq!(pending.next_request_id(), 1);
+    let rpc = WsRpc::new(Arc::new(Mutex::new(writer)), pending);
+    let handle = TokenHandle::default();
+    let renewals = tokio::spawn(forward_token_renewals(ResourceType::Trading, handle.subscribe(), rpc.clone()));
+    handle.set(token("first", false, false));
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    handle.set(token("second", false, false));
+    assert_eq!(
+        server.await.unwrap(),
+        vec!["authorize\n2\n\nfirst".to_string(), "authorize\n3\n\nsecond".to_string()]
+    );
+    renewals.abort();
+    let _ = rpc.writer().lock().await.close().await;
+}
+
+#[tokio::test]
+async fn test_market_data_only_token_renewal() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = tokio::spawn(serve_http_with_headers(
+        listener,
+        vec![(200, r#"{"mdAccessToken":"md2","expirationTime":"2099-01-01T00:00:00.000Z","hasMarketData":true}"#)],
+    ));
+    let client = client(Server::Demo, &format!("http://{}", address));
+    client.token.set(token("", false, false));
+    assert_eq!(client.token.renewal_token().as_deref(), Some("md"));
+    client.renew_access_token().await.unwrap();
+    assert_eq!(client.token.current().unwrap().md_access_token, "md2");
+    let requests = server.await.unwrap();
+    // the only request is the renewal, authorized with the market data token
+    assert_eq!(requests.len(), 1);
+    let head = requests[0].0.to_lowercase();
+    assert!(head.starts_with("post /v1/auth/renewaccesstoken"));
+    assert!(head.lines().any(|line| line.trim_end() == "authorization: bearer md"));
+}
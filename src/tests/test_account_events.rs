@@ -0,0 +1,122 @@
+use rust_decimal::Decimal;
+
+use crate::models::entity_event::{Entity, EntityEventType};
+use crate::models::execution::OrdStatus;
+use crate::websocket::feeds::AccountFeeds;
+use crate::websocket::process_account::parse_account_messages;
+use crate::websocket::rpc::PendingRequests;
+
+const ORDER_UPDATED: &str = r#"a[{"e":"props","d":{"entityType":"order","eventType":"Updated","entity":{"id":101,"accountId":7,"contractId":2665267,"timestamp":"2022-09-15T00:00:58.230Z","action":"Buy","ordStatus":"Filled","admin":false}}}]"#;
+const FILL_CREATED: &str = r#"a[{"e":"props","d":{"entityType":"fill","eventType":"Created","entity":{"id":301,"orderId":101,"contractId":2665267,"timestamp":"2022-09-15T00:00:59.000Z","tradeDate":{"year":2022,"month":9,"day":15},"action":"Buy","qty":2,"price":3956.25,"active":true,"finallyPaired":0}}},{"e":"props","d":{"entityType":"userProperty","eventType":"Deleted","entity":{"id":5}}}]"#;
+
+#[tokio::test]
+async fn test_props_events() {
+    let feeds = AccountFeeds::default();
+    let pending = PendingRequests::new();
+    let mut receiver = feeds.events.subscribe();
+    let mut order_events = feeds.events.subscribe_key(101);
+    parse_account_messages(ORDER_UPDATED, &feeds, &pending).unwrap();
+    parse_account_messages(FILL_CREATED, &feeds, &pending).unwrap();
+
+    let order = receiver.recv().await.unwrap();
+    assert_eq!(order, order_events.recv().await.unwrap());
+    assert_eq!(order.event_type, EntityEventType::Updated);
+    assert!(matches!(order.entity, Entity::Order(ref order) if order.ord_status == OrdStatus::Filled));
+
+    let fill = receiver.recv().await.unwrap();
+    assert_eq!(fill.entity_type, "fill");
+    assert!(matches!(fill.entity, Entity::Fill(ref fill) if fill.price == Decimal::new(395625, 2)));
+
+    let other = receiver.recv().await.unwrap();
+    assert_eq!(other.event_type, EntityEventType::Deleted);
+    assert_eq!(other.entity.id(), 5);
+}
+
+const POSITION_UPDATED: &str = r#"a[{"e":"props","d":{"entityType":"position","eventType":"Updated","entity":{"id":401,"accountId":7,"contractId":2665267,"timestamp":"2022-09-15T00:01:00.000Z","tradeDate":{"year":2022,"month":9,"day":15},"netPos":2,"netPrice":3956.25,"bought":2,"boughtValue":7912.5,"sold":0,"soldValue":0,"archived":false,"prevPos":0}}},{"e":"props","d":{"entityType":"order","eventType":"Created","entity":{"id":102,"accountId":7,"contractId":2665267,"timestamp":"2022-09-15T00:01:00.000Z","action":"Sell","ordStatus":"Working","admin":false}}}]"#;
+
+#[tokio::test]
+async fn test_account_state() {
+    use crate::models::user_data::{CashBalance, UserData};
+    let feeds = AccountFeeds::default();
+    let pending = PendingRequests::new();
+    let mut changes = feeds.state.subscribe();
+    feeds.state.seed(UserData {
+        cash_balances: vec![CashBalance {
+            id: 1,
+            account_id: 7,
+            amount: 5000.0,
+            realized_pn_l: 125.0,
+            ..Default::default()
+        }],
+        ..Default::default()
+    });
+    assert!(changes.has_changed().unwrap());
+    changes.mark_unchanged();
+    parse_account_messages(ORDER_UPDATED, &feeds, &pending).unwrap();
+    parse_account_messages(POSITION_UPDATED, &feeds, &pending).unwrap();
+    assert!(changes.has_changed().unwrap());
+
+    let state = &feeds.state;
+    assert_eq!(state.working_orders(2665267).iter().map(|o| o.id).collect::<Vec<_>>(), vec![102]);
+    assert_eq!(state.order(101).unwrap().ord_status, OrdStatus::Filled);
+    assert_eq!(state.net_position(7, 2665267), 2);
+    assert_eq!(state.net_position(8, 2665267), 0);
+    assert_eq!(state.average_price(7, 2665267), Some(Decimal::new(395625, 2)));
+    assert_eq!(state.average_price(8, 2665267), None);
+    assert_eq!(
+        state.open_pnl(7, 2665267, Decimal::new(3957, 0), Decimal::new(50, 0)),
+        Decimal::new(75, 0)
+    );
+    assert_eq!(state.realized_pnl(7), Decimal::new(125, 0));
+    assert_eq!(state.cash_balance(7).unwrap().amount, 5000.0);
+    assert!(state.margin(7).is_none());
+}
+
+#[tokio::test]
+async fn test_events_before_the_snapshot_are_kept() {
+    use crate::models::user_data::UserData;
+    let feeds = AccountFeeds::default();
+    let pending = PendingRequests::new();
+    parse_account_messages(POSITION_UPDATED, &feeds, &pending).unwrap();
+    assert!(feeds.state.order(102).is_none());
+    feeds.state.seed(UserData::default());
+    assert_eq!(feeds.state.order(102).unwrap().ord_status, OrdStatus::Working);
+    assert_eq!(feeds.state.net_position(7, 2665267), 2);
+}
+
+#[tokio::test]
+async fn test_stale_events_do_not_overwrite_the_snapshot() {
+    use chrono::{TimeZone, Utc};
+    use crate::models::execution::Order;
+    use crate::models::position::Position;
+    use crate::models::user_data::UserData;
+    let feeds = AccountFeeds::default();
+    let pending = PendingRequests::new();
+    parse_account_messages(ORDER_UPDATED, &feeds, &pending).unwrap();
+    parse_account_messages(POSITION_UPDATED, &feeds, &pending).unwrap();
+    let later = Utc.with_ymd_and_hms(2022, 9, 15, 0, 2, 0).unwrap();
+    feeds.state.seed(UserData {
+        orders: vec![Order {
+            id: 102,
+            account_id: 7,
+            contract_id: 2665267,
+            timestamp: Utc.with_ymd_and_hms(2022, 9, 15, 0, 1, 0).unwrap(),
+            ord_status: OrdStatus::Canceled,
+            ..Default::default()
+        }],
+        positions: vec![Position {
+            id: 401,
+            account_id: 7,
+            contract_id: 2665267,
+            timestamp: later,
+            net_pos: 1,
+            ..Default::default()
+        }],
+        ..Default::default()
+    });
+    // the queued position and order updates are older than the snapshot
+    assert_eq!(feeds.state.net_position(7, 2665267), 1);
+    assert_eq!(feeds.state.order(102).unwrap().ord_status, OrdStatus::Canceled);
+    // the snapshot has no copy of order 101, so its queued update still applies
+    assert_eq!(feeds.state.order(101).unwrap().ord_status, OrdStatus::Filled);
+}
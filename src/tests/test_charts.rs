@@ -0,0 +1,112 @@
+use chrono::TimeZone;
+use rust_decimal::Decimal;
+use serde_json::json;
+
+use crate::websocket::feeds::MarketDataFeeds;
+use crate::websocket::process_message::parse_messages;
+use crate::websocket::requests::{ChartDescription, MarketDataRequest, TimeRange};
+use crate::websocket::rpc::PendingRequests;
+
+const BAR_FRAME: &str = r#"a[{"e":"chart","d":{"charts":[{"id":41,"td":20220915,"bars":[{"timestamp":"2022-09-15T13:30:00.000Z","open":3956.5,"high":3960.25,"low":3955.0,"close":3959.75,"upVolume":1200,"downVolume":800,"upTicks":310,"downTicks":290,"bidVolume":900,"offerVolume":1100}]}]}}]"#;
+
+#[tokio::test]
+async fn test_chart_request_body() {
+    let timestamp = chrono::Utc.with_ymd_and_hms(2022, 9, 15, 13, 30, 0).unwrap();
+    let request = MarketDataRequest::chart(
+        "ESZ2",
+        ChartDescription::minute_bars(5),
+        TimeRange::closest_timestamp(timestamp).and_as_much_as_elements(100),
+    );
+    assert_eq!(
+        request.subscribe_body(),
+        json!({
+            "symbol": "ESZ2",
+            "chartDescription": {
+                "underlyingType": "MinuteBar",
+                "elementSize": 5,
+                "elementSizeUnit": "UnderlyingUnits",
+                "withHistogram": false
+            },
+            "timeRange": {
+                "closestTimestamp": "2022-09-15T13:30:00Z",
+                "asMuchAsElements": 100
+            }
+        })
+    );
+    let range = MarketDataRequest::chart("ESZ2", ChartDescription::range_bars(8), TimeRange::closest_tick_id(12));
+    assert_eq!(range.subscribe_body()["chartDescription"]["elementSizeUnit"], "Range");
+    assert_eq!(range.subscribe_body()["timeRange"], json!({"closestTickId": 12}));
+    assert!(ChartDescription::default().is_tick_stream());
+    assert!(!ChartDescription::volume_bars(500).is_tick_stream());
+    assert_ne!(request.subscription_key(), range.subscription_key());
+}
+
+#[tokio::test]
+async fn test_bar_packets() {
+    let feeds = MarketDataFeeds::default();
+    let pending = PendingRequests::new();
+    let mut bars = feeds.bars.subscribe_key(41);
+    parse_messages(BAR_FRAME.to_string(), &feeds, &pending).unwrap();
+    let bar = bars.recv().await.unwrap();
+    assert_eq!(bar.historical_id, 41);
+    assert_eq!(bar.close, Decimal::new(395975, 2));
+    assert_eq!(bar.volume(), Decimal::new(2000, 0));
+    assert_eq!(bar.delta(), Decimal::new(200, 0));
+}
+
+#[tokio::test]
+async fn test_tick_stream_deduplicated() {
+    use crate::models::time_and_sales::TickEvent;
+    const HISTORY: &str = r#"a[{"e":"chart","d":{"charts":[{"id":32,"td":20220915,"bp":15826,"bt":1663200058230,"ts":0.25,"tks":[{"t":5,"p":-1,"s":1,"b":-1,"a":0,"id":2},{"t":0,"p":0,"s":2,"b":-1,"a":0,"id":1}]}]}}]"#;
+    const OVERLAP: &str = r#"a[{"e":"chart","d":{"charts":[{"id":32,"td":20220915,"bp":15826,"bt":1663200058230,"ts":0.25,"tks":[{"t":5,"p":-1,"s":1,"b":-1,"a":0,"id":2},{"t":9,"p":1,"s":4,"b":0,"a":1,"id":3}]},{"id":32,"eoh":true}]}}]"#;
+    let feeds = MarketDataFeeds::default();
+    let pending = PendingRequests::new();
+    let mut ticks = feeds.ticks.subscribe_key(32);
+    parse_messages(HISTORY.to_string(), &feeds, &pending).unwrap();
+    parse_messages(OVERLAP.to_string(), &feeds, &pending).unwrap();
+    let mut tick_ids = Vec::new();
+    loop {
+        match ticks.recv().await.unwrap() {
+            TickEvent::Trade(item) => tick_ids.push(item.tick_id),
+            TickEvent::EndOfHistory(historical_id) => {
+                assert_eq!(historical_id, 32);
+                break;
+            }
+        }
+    }
+    assert_eq!(tick_ids, vec![1, 2, 3]);
+    assert!(feeds.tick_tape.lock().unwrap().is_live(32));
+}
+
+#[tokio::test]
+async fn test_late_ticks_are_delivered_and_cancelled_charts_forgotten() {
+    use crate::models::time_and_sales::TickEvent;
+    use crate::websocket::requests::{MarketData, MarketDataRequest};
+    use crate::websocket::subscriptions::MarketDataSubscriptions;
+    const NEWER: &str = r#"a[{"e":"chart","d":{"charts":[{"id":32,"td":20220915,"bp":15826,"bt":1663200058230,"ts":0.25,"tks":[{"t":9,"p":1,"s":4,"b":0,"a":1,"id":3}]},{"id":32,"eoh":true}]}}]"#;
+    const LATE: &str = r#"a[{"e":"chart","d":{"charts":[{"id":32,"td":20220915,"bp":15826,"bt":1663200058230,"ts":0.25,"tks":[{"t":5,"p":-1,"s":1,"b":-1,"a":0,"id":2},{"t":9,"p":1,"s":4,"b":0,"a":1,"id":3}]}]}}]"#;
+    let feeds = MarketDataFeeds::default();
+    let pending = PendingRequests::new();
+    let mut ticks = feeds.ticks.subscribe_key(32);
+    parse_messages(NEWER.to_string(), &feeds, &pending).unwrap();
+    parse_messages(LATE.to_string(), &feeds, &pending).unwrap();
+    // the late tick 2 comes after 3 and the end of history, the resent 3 is dropped
+    let mut events = Vec::new();
+    for _ in 0..3 {
+        events.push(ticks.recv().await.unwrap());
+    }
+    assert!(matches!(&events[0], TickEvent::Trade(item) if item.tick_id == 3));
+    assert_eq!(events[1], TickEvent::EndOfHistory(32));
+    assert!(matches!(&events[2], TickEvent::Trade(item) if item.tick_id == 2));
+
+    let mut chart = MarketDataRequest::new(MarketData::Chart, "ESZ2");
+    chart.historical_id = 32;
+    let subscriptions = MarketDataSubscriptions::new(&[chart.clone()]).with_tick_tape(feeds.tick_tape.clone());
+    assert!(feeds.tick_tape.lock().unwrap().is_live(32));
+    subscriptions.unsubscribe(&chart).await.unwrap();
+    assert!(!feeds.tick_tape.lock().unwrap().is_live(32));
+    parse_messages(LATE.to_string(), &feeds, &pending).unwrap();
+    for tick_id in [2, 3] {
+        assert!(matches!(ticks.recv().await.unwrap(), TickEvent::Trade(item) if item.tick_id == tick_id));
+    }
+}
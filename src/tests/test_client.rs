@@ -4,15 +4,15 @@ use crate::models::orders::OrderTicket;
 
 #[tokio::test]
 async fn test_auth_token() {
-    let mut client = crate::client::TradovateClient::load_from_env(crate::client::Server::Live);
+    let client = crate::client::TradovateClient::load_from_env(crate::client::Server::Live).unwrap();
     client.authenticate().await.unwrap();
-    println!("{:#?}", client.access_token_info);
-    assert!(client.access_token_info.is_some())
+    println!("{:#?}", client.token.current());
+    assert!(client.token.is_set())
 }
 
 #[tokio::test]
 async fn test_contract_deps() {
-    let mut client = crate::client::TradovateClient::load_from_env(crate::client::Server::Live);
+    let client = crate::client::TradovateClient::load_from_env(crate::client::Server::Live).unwrap();
     client.authenticate().await.unwrap();
     let deps = client.get_contract_deps().await;
     println!("{:#?}", deps);
@@ -21,7 +21,7 @@ async fn test_contract_deps() {
 
 #[tokio::test]
 async fn test_products_list() {
-    let mut client = crate::client::TradovateClient::load_from_env(crate::client::Server::Live);
+    let client = crate::client::TradovateClient::load_from_env(crate::client::Server::Live).unwrap();
     client.authenticate().await.unwrap();
     let list = client.get_products_list().await;
     println!("{:#?}", list);
@@ -30,7 +30,7 @@ async fn test_products_list() {
 
 #[tokio::test]
 async fn test_find_contract() {
-    let mut client = crate::client::TradovateClient::load_from_env(crate::client::Server::Live);
+    let client = crate::client::TradovateClient::load_from_env(crate::client::Server::Live).unwrap();
     client.authenticate().await.unwrap();
     let contract = client.find_contract("ESZ2").await;
     println!("{:#?}", contract);
@@ -39,7 +39,7 @@ async fn test_find_contract() {
 
 #[tokio::test]
 async fn test_find_maturity() {
-    let mut client = crate::client::TradovateClient::load_from_env(crate::client::Server::Live);
+    let client = crate::client::TradovateClient::load_from_env(crate::client::Server::Live).unwrap();
     client.authenticate().await.unwrap();
     let maturity = client.find_maturity(46023).await;
     println!("{:#?}", maturity);
@@ -48,7 +48,7 @@ async fn test_find_maturity() {
 
 #[tokio::test]
 async fn test_positions() {
-    let mut client = crate::client::TradovateClient::load_from_env(crate::client::Server::Demo);
+    let client = crate::client::TradovateClient::load_from_env(crate::client::Server::Demo).unwrap();
     client.authenticate().await.unwrap();
     let positions = client.get_positions().await;
     println!("{:#?}", positions);
@@ -59,7 +59,7 @@ async fn test_positions() {
 async fn test_place_order() {
     //set env logger to debug
     log4rs::init_file("log_config.yaml", Default::default()).unwrap();
-    let mut client = crate::client::TradovateClient::load_from_env(crate::client::Server::Demo);
+    let client = crate::client::TradovateClient::load_from_env(crate::client::Server::Demo).unwrap();
     client.authenticate().await.unwrap();
     let balances = client.get_cash_balances().await.unwrap();
     let order = client.place_order(OrderTicket::market_sell(&client.username,balances[0].account_id,"ESH3", 1)).await;
@@ -70,7 +70,7 @@ async fn test_place_order() {
 
 #[tokio::test]
 async fn test_accounts_list() {
-    let mut client = crate::client::TradovateClient::load_from_env(crate::client::Server::Live);
+    let client = crate::client::TradovateClient::load_from_env(crate::client::Server::Live).unwrap();
     client.authenticate().await.unwrap();
     let accounts = client.get_accounts_list().await;
     println!("{:#?}", accounts);
@@ -79,7 +79,7 @@ async fn test_accounts_list() {
 
 #[tokio::test]
 async fn test_balance_list() {
-    let mut client = crate::client::TradovateClient::load_from_env(crate::client::Server::Demo);
+    let client = crate::client::TradovateClient::load_from_env(crate::client::Server::Demo).unwrap();
     client.authenticate().await.unwrap();
     let balances = client.get_cash_balances().await;
     println!("{:#?}", balances);
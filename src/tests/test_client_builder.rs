@@ -0,0 +1,170 @@
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::client::{Protocol, ResourceType, Server, TradovateClient};
+use crate::client_builder::TradovateClientBuilder;
+use crate::config::{ClientConfig, Credentials, ServerUrls};
+use crate::error::Error;
+use crate::rest::retry::RetryPolicy;
+
+/// A stand-in http server answering each connection with the next of `responses`,
+/// returns the request line and body of every request it received.
+pub(crate) async fn serve_http(listener: tokio::net::TcpListener, responses: Vec<(u16, &'static str)>) -> Vec<(String, String)> {
+    serve_http_with_headers(listener, responses)
+        .await
+        .into_iter()
+        .map(|(head, body)| (head.lines().next().unwrap().to_string(), body))
+        .collect()
+}
+
+/// Same as `serve_http`, but returns the request line and headers of every request instead of only its request line.
+pub(crate) async fn serve_http_with_headers(listener: tokio::net::TcpListener, responses: Vec<(u16, &'static str)>) -> Vec<(String, String)> {
+    let mut requests = Vec::new();
+    for (status, body) in responses {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buffer = [0; 4096];
+        let (head, content_length) = loop {
+            let read = stream.read(&mut buffer).await.unwrap();
+            request.extend_from_slice(&buffer[..read]);
+            let text = String::from_utf8_lossy(&request).to_string();
+            if let Some(end) = text.find("\r\n\r\n") {
+                let content_length = text[..end]
+                    .lines()
+                    .find_map(|line| line.to_lowercase().strip_prefix("content-length: ").map(str::to_string))
+                    .map_or(0, |length| length.trim().parse::<usize>().unwrap());
+                break (end + 4, content_length);
+            }
+        };
+        while request.len() < head + content_length {
+            let read = stream.read(&mut buffer).await.unwrap();
+            request.extend_from_slice(&buffer[..read]);
+        }
+        let text = String::from_utf8_lossy(&request).to_string();
+        requests.push((text[..head].trim_end().to_string(), text[head..].to_string()));
+        let response = format!(
+            "HTTP/1.1 {} OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+    }
+    requests
+}
+
+pub(crate) fn credentials() -> Credentials {
+    Credentials {
+        app_id: "app".to_string(),
+        app_version: "1.0".to_string(),
+        cid: 8,
+        secret: "secret".to_string(),
+        username: "user".to_string(),
+        password: "password".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_server_urls() {
+    let urls = ServerUrls::for_server(&Server::Demo);
+    assert_eq!(urls.url(ResourceType::Trading, Protocol::Https), "https://demo.tradovateapi.com");
+    assert_eq!(
+        urls.url(ResourceType::MarketData, Protocol::Wss),
+        "wss://md.tradovateapi.com/v1/websocket"
+    );
+    let local = ServerUrls::all("http://127.0.0.1:8080/");
+    assert_eq!(local.url(ResourceType::Trading, Protocol::Https), "http://127.0.0.1:8080");
+    assert_eq!(local.url(ResourceType::MarketReplay, Protocol::Wss), "ws://127.0.0.1:8080/v1/websocket");
+}
+
+#[tokio::test]
+async fn test_config_files() {
+    let dir = std::env::temp_dir().join(format!("tradovate_rs_config_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let toml_path = dir.join("client.toml");
+    std::fs::write(
+        &toml_path,
+        r#"
+server = "live"
+app_id = "app"
+app_version = "1.0"
+cid = 8
+secret = "secret"
+username = "user"
+password = "password"
+device_id = "bot"
+
+[urls]
+trading = "http://127.0.0.1:1"
+market_data = "http://127.0.0.1:2"
+market_replay = "http://127.0.0.1:3"
+"#,
+    )
+    .unwrap();
+    let config = ClientConfig::from_file(&toml_path).unwrap();
+    assert_eq!(config.server, Server::Live);
+    assert_eq!(config.credentials, credentials());
+    assert_eq!(config.urls.as_ref().unwrap().market_data, "http://127.0.0.1:2");
+    let client = TradovateClient::load_from_file(&toml_path).unwrap();
+    assert_eq!(client.device_id, "bot");
+    assert_eq!(client.url(ResourceType::Trading, Protocol::Https), "http://127.0.0.1:1");
+
+    let yaml_path = dir.join("client.yaml");
+    std::fs::write(
+        &yaml_path,
+        "app_id: app\napp_version: '1.0'\ncid: 8\nsecret: secret\nusername: user\npassword: password\ntoken_cache: /tmp/tokens\n",
+    )
+    .unwrap();
+    let config = ClientConfig::from_file(&yaml_path).unwrap();
+    assert_eq!(config.server, Server::Demo);
+    assert_eq!(config.token_cache.unwrap().to_str(), Some("/tmp/tokens"));
+
+    let missing_cid = dir.join("missing.yml");
+    std::fs::write(&missing_cid, "app_id: app\n").unwrap();
+    assert!(matches!(ClientConfig::from_file(&missing_cid), Err(Error::Config(_))));
+    assert!(matches!(ClientConfig::from_file(dir.join("client.json")), Err(Error::Config(_))));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_login_against_local_server() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = tokio::spawn(serve_http(
+        listener,
+        vec![
+            (200, r#"{"p-ticket":"abc","p-time":0}"#),
+            (
+                200,
+                r#"{"accessToken":"token","mdAccessToken":"md","expirationTime":"2099-01-01T00:00:00.000Z","userId":3}"#,
+            ),
+            (503, "Service Unavailable"),
+            (200, "[]"),
+        ],
+    ));
+    let token_cache = std::env::temp_dir().join(format!("tradovate_rs_tokens_{}", std::process::id()));
+    let client = TradovateClientBuilder::new(Server::Demo)
+        .credentials(credentials())
+        .base_url(&format!("http://{}", address))
+        .device_id("bot")
+        .token_cache(&token_cache)
+        .retry_policy(RetryPolicy::new(1, Duration::from_millis(1), Duration::from_millis(1)))
+        .build()
+        .unwrap();
+    client.login().await.unwrap();
+    assert_eq!(client.token.access_token().as_deref(), Some("token"));
+    assert!(token_cache.join("tradovate_auth_demo_user.json").exists());
+    assert!(client.get_products_list().await.unwrap().is_empty());
+
+    let requests = server.await.unwrap();
+    assert!(requests[0].0.starts_with("POST /v1/auth/accesstokenrequest"));
+    let first: serde_json::Value = serde_json::from_str(&requests[0].1).unwrap();
+    assert_eq!(first["deviceId"], "bot");
+    assert!(first.get("p-ticket").is_none());
+    let second: serde_json::Value = serde_json::from_str(&requests[1].1).unwrap();
+    assert_eq!(second["p-ticket"], "abc");
+    assert!(requests[2].0.starts_with("GET /v1/product/list"));
+    assert!(requests[3].0.starts_with("GET /v1/product/list"));
+    std::fs::remove_dir_all(&token_cache).unwrap();
+}
@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use reqwest::StatusCode;
+
+use crate::client::check_response;
+use crate::error::Error;
+use crate::websocket::rpc::WsResponse;
+
+#[tokio::test]
+async fn test_check_response_classifies_errors() {
+    let body = r#"{"id":1}"#.to_string();
+    assert_eq!(check_response(StatusCode::OK, body.clone()).unwrap(), body);
+    assert!(matches!(
+        check_response(StatusCode::OK, r#"{"errorText":"Incorrect username or password"}"#.to_string()),
+        Err(Error::Api { status: 200, message }) if message == "Incorrect username or password"
+    ));
+    assert!(matches!(
+        check_response(StatusCode::OK, r#"{"p-ticket":"abc","p-time":15}"#.to_string()),
+        Err(Error::RateLimited { ticket: Some(ticket), wait: Some(wait) })
+            if ticket == "abc" && wait == Duration::from_secs(15)
+    ));
+    assert!(matches!(
+        check_response(StatusCode::OK, r#"{"p-ticket":"abc","p-time":15,"p-captcha":true}"#.to_string()),
+        Err(Error::CaptchaRequired { ticket: Some(ticket) }) if ticket == "abc"
+    ));
+    assert!(matches!(
+        check_response(StatusCode::UNAUTHORIZED, "Access is denied".to_string()),
+        Err(Error::Auth(message)) if message == "Access is denied"
+    ));
+    assert!(matches!(
+        check_response(StatusCode::TOO_MANY_REQUESTS, String::new()),
+        Err(Error::RateLimited { ticket: None, wait: None })
+    ));
+    assert!(matches!(
+        check_response(StatusCode::NOT_FOUND, "Not found".to_string()),
+        Err(Error::Api { status: 404, .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_websocket_errors_convert() {
+    let rejected = WsResponse {
+        status: 404,
+        request_id: 3,
+        data: serde_json::json!("Not found"),
+    };
+    assert!(matches!(rejected.into_error(), Error::Api { status: 404, message } if message == "Not found"));
+    assert!(matches!(
+        Error::from(tokio_tungstenite::tungstenite::Error::AlreadyClosed),
+        Error::ConnectionClosed
+    ));
+}
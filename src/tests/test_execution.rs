@@ -0,0 +1,28 @@
+use rust_decimal::Decimal;
+
+use crate::models::execution::{CommandReport, CommandStatus, ExecType, ExecutionReport, Fill, OrdStatus, Order, OrderVersion};
+use crate::models::orders::{OrderAction, OrderType};
+
+#[tokio::test]
+async fn test_order_entities() {
+    let order = serde_json::from_str::<Order>(r#"{"id":101,"accountId":7,"contractId":2665267,"timestamp":"2022-09-15T00:00:58.230Z","action":"Buy","ordStatus":"Working","admin":false}"#).unwrap();
+    assert_eq!(order.ord_status, OrdStatus::Working);
+    assert!(order.ord_status.is_working());
+    let version = serde_json::from_str::<OrderVersion>(r#"{"id":201,"orderId":101,"orderQty":2,"orderType":"StopLimit","price":3956.25,"stopPrice":3956.0,"timeInForce":"GTC"}"#).unwrap();
+    assert_eq!(version.order_type, OrderType::StopLimit);
+    assert_eq!(version.price, Some(Decimal::new(395625, 2)));
+    let fill = serde_json::from_str::<Fill>(r#"{"id":301,"orderId":101,"contractId":2665267,"timestamp":"2022-09-15T00:00:59.000Z","tradeDate":{"year":2022,"month":9,"day":15},"action":"Buy","qty":2,"price":3956.25,"active":true,"finallyPaired":0}"#).unwrap();
+    assert_eq!(fill.action, OrderAction::Buy);
+    assert_eq!(fill.trade_date, chrono::NaiveDate::from_ymd_opt(2022, 9, 15).unwrap());
+}
+
+#[tokio::test]
+async fn test_reports() {
+    let report = serde_json::from_str::<ExecutionReport>(r#"{"id":401,"commandId":501,"name":"Trade","accountId":7,"contractId":2665267,"timestamp":"2022-09-15T00:00:59.000Z","tradeDate":{"year":2022,"month":9,"day":15},"orderId":101,"execType":"Trade","ordStatus":"Filled","action":"Buy","cumQty":2,"avgPx":3956.25,"lastQty":2,"lastPx":3956.25}"#).unwrap();
+    assert_eq!(report.exec_type, ExecType::Trade);
+    assert!(report.ord_status.unwrap().is_done());
+    let rejected = serde_json::from_str::<CommandReport>(r#"{"id":601,"commandId":501,"timestamp":"2022-09-15T00:00:59.000Z","commandStatus":"RiskRejected","rejectReason":"AccountClosed","text":"Account is closed"}"#).unwrap();
+    assert!(rejected.command_status.is_rejected());
+    let unknown = serde_json::from_str::<CommandReport>(r#"{"commandStatus":"SomethingNew"}"#).unwrap();
+    assert_eq!(unknown.command_status, CommandStatus::Unknown);
+}
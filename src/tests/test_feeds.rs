@@ -0,0 +1,68 @@
+use futures::StreamExt;
+
+use crate::websocket::feeds::{Feed, FeedConfig, FeedError};
+
+#[tokio::test]
+async fn test_feed_publish_by_key() {
+    let feed = Feed::<i64>::new(FeedConfig::default());
+    let mut all = feed.subscribe();
+    let mut only_es = feed.subscribe_key(1);
+    feed.publish(1, 10);
+    feed.publish(2, 20);
+    feed.publish(1, 11);
+    assert_eq!(all.recv().await, Ok(10));
+    assert_eq!(all.recv().await, Ok(20));
+    assert_eq!(all.recv().await, Ok(11));
+    assert_eq!(only_es.recv().await, Ok(10));
+    assert_eq!(only_es.recv().await, Ok(11));
+}
+
+#[tokio::test]
+async fn test_feed_drops_keys_without_subscribers() {
+    let feed = Feed::<i64>::new(FeedConfig::default());
+    let first = feed.subscribe_key(1);
+    let second = feed.subscribe_key(2);
+    assert_eq!(feed.subscribed_keys(), vec![1, 2]);
+    drop(first);
+    feed.publish(1, 10);
+    assert_eq!(feed.subscribed_keys(), vec![2]);
+    drop(second);
+    let _third = feed.subscribe_key(3);
+    assert_eq!(feed.subscribed_keys(), vec![3]);
+}
+
+#[tokio::test]
+async fn test_feed_bounded_history() {
+    let feed = Feed::<i64>::new(FeedConfig { buffer_size: 8, history_size: Some(3) });
+    for i in 0..5 {
+        feed.publish(1, i);
+    }
+    assert_eq!(feed.history(), vec![2, 3, 4]);
+    assert_eq!(feed.last(), Some(4));
+    let no_history = Feed::<i64>::new(FeedConfig::default());
+    no_history.publish(1, 1);
+    assert!(no_history.history().is_empty());
+}
+
+#[tokio::test]
+async fn test_feed_lag_reporting() {
+    let feed = Feed::<i64>::new(FeedConfig { buffer_size: 2, history_size: None });
+    let mut receiver = feed.subscribe();
+    for i in 0..5 {
+        feed.publish(1, i);
+    }
+    assert_eq!(receiver.recv().await, Err(FeedError::Lagged(3)));
+    assert_eq!(receiver.recv().await, Ok(3));
+    assert_eq!(receiver.lagged(), 3);
+}
+
+#[tokio::test]
+async fn test_feed_stream_ends_when_dropped() {
+    let feed = Feed::<i64>::new(FeedConfig::default());
+    let stream = feed.subscribe().into_stream();
+    feed.publish(1, 1);
+    feed.publish(1, 2);
+    drop(feed);
+    let items = stream.collect::<Vec<_>>().await;
+    assert_eq!(items, vec![Ok(1), Ok(2)]);
+}
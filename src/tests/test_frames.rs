@@ -0,0 +1,57 @@
+use crate::models::orderbook::OrderBooks;
+use crate::models::tick_chart::ChartData;
+use crate::error::Error;
+use crate::websocket::frames::{decode_frame, Event, Frame};
+use crate::websocket::process_message::event_key;
+
+const DOM_FRAME: &str = r#"a[{"e":"md","d":{"doms":[{"contractId":2665267,"timestamp":"2022-09-15T00:00:58.230Z","bids":[{"price":3956.5,"size":12},{"price":3956.25,"size":30}],"offers":[{"price":3956.75,"size":9}]}]}}]"#;
+const CHART_AND_RESPONSE_FRAME: &str = r#"a[{"s":200,"i":3,"d":{"historicalId":32,"realtimeId":31}},{"e":"chart","d":{"charts":[{"id":32,"td":20220915,"bp":15826,"bt":1663200058230,"ts":0.25,"s":"db","tks":[{"t":0,"p":0,"s":2,"b":-1,"a":0,"bs":12,"as":9,"id":1},{"t":5,"p":-1,"s":1,"b":-1,"a":0,"bs":11,"as":9,"id":2}]},{"id":32,"eoh":true}]}}]"#;
+
+#[tokio::test]
+async fn test_decode_open_heartbeat_close() {
+    assert_eq!(decode_frame("o").unwrap(), Frame::Open);
+    assert_eq!(decode_frame("h").unwrap(), Frame::Heartbeat);
+    assert_eq!(
+        decode_frame(r#"c[1000,"Normal closure"]"#).unwrap(),
+        Frame::Close { code: 1000, reason: "Normal closure".to_string() }
+    );
+}
+
+#[tokio::test]
+async fn test_decode_invalid_frames() {
+    assert!(matches!(decode_frame(""), Err(Error::Protocol(_))));
+    assert!(matches!(decode_frame("x[]"), Err(Error::Protocol(_))));
+    assert!(matches!(decode_frame("a[{"), Err(Error::Json(_))));
+}
+
+#[tokio::test]
+async fn test_decode_md_event() {
+    let Frame::Array(events) = decode_frame(DOM_FRAME).unwrap() else { panic!("expected an array frame") };
+    assert_eq!(events.len(), 1);
+    let Event::Data { name, data } = events[0].clone() else { panic!("expected a data event") };
+    assert_eq!(name, "md");
+    assert_eq!(event_key(&name, &data).unwrap(), "doms");
+    let books = serde_json::from_value::<OrderBooks>(data).unwrap();
+    assert_eq!(books.doms[0].bids.len(), 2);
+    assert_eq!(books.doms[0].asks[0].size, 9);
+}
+
+#[tokio::test]
+async fn test_decode_every_event_in_array() {
+    let Frame::Array(events) = decode_frame(CHART_AND_RESPONSE_FRAME).unwrap() else { panic!("expected an array frame") };
+    assert_eq!(events.len(), 2);
+    let Event::Response(response) = &events[0] else { panic!("expected a response") };
+    assert_eq!(response.request_id, 3);
+    assert_eq!(response.data["historicalId"], 32);
+    let Event::Data { name, data } = events[1].clone() else { panic!("expected a data event") };
+    assert_eq!(event_key(&name, &data).unwrap(), "chart");
+    let charts = serde_json::from_value::<ChartData>(data).unwrap();
+    assert_eq!(charts.get_all_ts_items().len(), 2);
+    assert!(charts.charts[1].eoh);
+}
+
+#[tokio::test]
+async fn test_decode_unknown_event() {
+    let Frame::Array(events) = decode_frame(r#"a[{"x":1}]"#).unwrap() else { panic!("expected an array frame") };
+    assert!(matches!(events[0], Event::Unknown(_)));
+}
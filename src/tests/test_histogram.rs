@@ -0,0 +1,72 @@
+use rust_decimal::Decimal;
+
+use crate::websocket::feeds::MarketDataFeeds;
+use crate::websocket::process_message::parse_messages;
+use crate::websocket::rpc::PendingRequests;
+
+const FULL_HISTOGRAM: &str = r#"a[{"e":"md","d":{"histograms":[{"contractId":2665267,"timestamp":"2022-09-15T00:00:58.230Z","tradeDate":{"year":2022,"month":9,"day":15},"base":3956.5,"items":{"-2":100.0,"0":350.0,"3":80.0},"refresh":true}]}}]"#;
+const HISTOGRAM_UPDATE: &str = r#"a[{"e":"md","d":{"histograms":[{"contractId":2665267,"timestamp":"2022-09-15T00:00:59.230Z","tradeDate":{"year":2022,"month":9,"day":15},"base":3956.5,"items":{"3":500.0,"4":2.0},"refresh":false}]}}]"#;
+
+#[tokio::test]
+async fn test_histogram_updates_are_merged() {
+    let tick_size = Decimal::new(25, 2);
+    let feeds = MarketDataFeeds::default();
+    let pending = PendingRequests::new();
+    let mut histograms = feeds.histograms.subscribe();
+    parse_messages(FULL_HISTOGRAM.to_string(), &feeds, &pending).unwrap();
+    parse_messages(HISTOGRAM_UPDATE.to_string(), &feeds, &pending).unwrap();
+    let first = histograms.recv().await.unwrap();
+    assert_eq!(first.low(tick_size), Some(Decimal::new(39560, 1)));
+    assert_eq!(first.point_of_control(tick_size), Some(Decimal::new(39565, 1)));
+    let merged = histograms.recv().await.unwrap();
+    assert_eq!(merged.items.len(), 4);
+    assert_eq!(merged.high(tick_size), Some(Decimal::new(39575, 1)));
+    assert_eq!(merged.volume_at(Decimal::new(395725, 2), tick_size), Decimal::new(500, 0));
+    assert_eq!(merged.point_of_control(tick_size), Some(Decimal::new(395725, 2)));
+    assert_eq!(merged.total_volume(), Decimal::new(952, 0));
+    assert_eq!(feeds.last_histograms.get(2665267), Some(merged));
+}
+
+const MOVED_BASE: &str = r#"a[{"e":"md","d":{"histograms":[{"contractId":2665267,"timestamp":"2022-09-15T00:01:00.230Z","tradeDate":{"year":2022,"month":9,"day":15},"base":3957.0,"items":{"1":10.0},"refresh":false}]}}]"#;
+
+#[tokio::test]
+async fn test_histogram_rebased_on_a_new_base() {
+    let tick_size = Decimal::new(25, 2);
+    let feeds = MarketDataFeeds::default();
+    let pending = PendingRequests::new();
+    feeds.last_histograms.set_tick_size(2665267, tick_size);
+    parse_messages(FULL_HISTOGRAM.to_string(), &feeds, &pending).unwrap();
+    parse_messages(MOVED_BASE.to_string(), &feeds, &pending).unwrap();
+    let merged = feeds.last_histograms.get(2665267).unwrap();
+    assert_eq!(merged.base, Decimal::new(39570, 1));
+    // the old volumes keep their prices, the update overwrites the one at 3957.25
+    assert_eq!(merged.volume_at(Decimal::new(39560, 1), tick_size), Decimal::new(100, 0));
+    assert_eq!(merged.volume_at(Decimal::new(39565, 1), tick_size), Decimal::new(350, 0));
+    assert_eq!(merged.volume_at(Decimal::new(395725, 2), tick_size), Decimal::new(10, 0));
+    assert_eq!(merged.total_volume(), Decimal::new(460, 0));
+}
+
+#[tokio::test]
+async fn test_replay_histograms_are_published() {
+    use crate::models::{orderbook::new_orderbooks_rwl, quotes::new_quotes_rwl, time_and_sales::new_time_and_sales_rwl};
+    use crate::websocket::process_replay_ms::parse_replay_messages;
+    let feeds = MarketDataFeeds::default();
+    let pending = PendingRequests::new();
+    let mut histograms = feeds.histograms.subscribe_key(2665267);
+    for message in [FULL_HISTOGRAM, HISTOGRAM_UPDATE] {
+        let complete = parse_replay_messages(
+            message.to_string(),
+            new_orderbooks_rwl(),
+            new_time_and_sales_rwl(),
+            new_quotes_rwl(),
+            &feeds,
+            chrono::Utc::now(),
+            &pending,
+        )
+        .await
+        .unwrap();
+        assert!(!complete);
+    }
+    histograms.recv().await.unwrap();
+    assert_eq!(histograms.recv().await.unwrap().total_volume(), Decimal::new(952, 0));
+}
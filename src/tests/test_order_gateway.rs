@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use futures::{SinkExt, StreamExt};
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::models::orders::{CancelOrder, OrderAction, OrderTicket, OrderValidationError};
+use crate::websocket::connection::keep_listening_account;
+use crate::websocket::feeds::AccountFeeds;
+use crate::error::Error;
+use crate::websocket::order_gateway::OrderGateway;
+use crate::websocket::rpc::{PendingRequests, WsRpc};
+
+/// Answers each request frame of the socket with the next status and data of `responses`.
+async fn serve(listener: tokio::net::TcpListener, responses: Vec<(u16, &'static str)>) -> Vec<String> {
+    let (stream, _) = listener.accept().await.unwrap();
+    let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+    let mut endpoints = Vec::new();
+    for (status, response) in responses {
+        let Some(Ok(Message::Text(frame))) = ws.next().await else {
+            break;
+        };
+        let mut lines = frame.split('\n');
+        endpoints.push(lines.next().unwrap().to_string());
+        let request_id = lines.next().unwrap();
+        let reply = format!(r#"a[{{"s":{},"i":{},"d":{}}}]"#, status, request_id, response);
+        ws.send(Message::Text(reply)).await.unwrap();
+    }
+    endpoints
+}
+
+#[tokio::test]
+async fn test_order_gateway() {
+    let gateway = OrderGateway::new();
+    let ticket = OrderTicket::market_buy("user", 7, "ESZ2", 1);
+    assert!(matches!(gateway.place_order(&ticket).await, Err(Error::ConnectionClosed)));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = tokio::spawn(serve(
+        listener,
+        vec![
+            (200, r#"{"orderId":55}"#),
+            (200, r#"{"failureReason":"UnknownReason","failureText":"Order is not working"}"#),
+            (200, r#"{}"#),
+            (401, r#""Access is denied""#),
+        ],
+    ));
+    let (ws, _) = tokio_tungstenite::connect_async(format!("ws://{}", address)).await.unwrap();
+    let (writer, reader) = ws.split();
+    let pending = Arc::new(PendingRequests::new());
+    tokio::spawn(keep_listening_account(reader, AccountFeeds::default(), pending.clone()));
+    gateway.attach(WsRpc::new(Arc::new(Mutex::new(writer)), pending)).await;
+
+    let invalid = OrderTicket {
+        action: OrderAction::Dont,
+        ..ticket.clone()
+    };
+    assert!(matches!(
+        gateway.place_order(&invalid).await,
+        Err(Error::InvalidOrder(OrderValidationError::MissingAction))
+    ));
+    assert_eq!(gateway.place_order(&ticket).await.unwrap(), 55);
+    match gateway.cancel_order(&CancelOrder::new(55)).await {
+        Err(Error::OrderRejected { reason, text }) => {
+            assert_eq!(reason, "UnknownReason");
+            assert_eq!(text.as_deref(), Some("Order is not working"));
+        }
+        other => panic!("expected a rejection, got {:?}", other),
+    }
+    // an acknowledgement without a command id
+    assert!(matches!(gateway.cancel_order(&CancelOrder::new(55)).await, Err(Error::Protocol(_))));
+    // an error status is not a rejection of the order
+    match gateway.cancel_order(&CancelOrder::new(55)).await {
+        Err(Error::Api { status, message }) => {
+            assert_eq!(status, 401);
+            assert_eq!(message, "Access is denied");
+        }
+        other => panic!("expected an api error, got {:?}", other),
+    }
+    assert_eq!(
+        server.await.unwrap(),
+        vec!["order/placeorder", "order/cancelorder", "order/cancelorder", "order/cancelorder"]
+    );
+}
+
+#[tokio::test]
+async fn test_dropped_account_socket_is_an_error() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        // dropped without a close handshake
+        drop(tokio_tungstenite::accept_async(stream).await.unwrap());
+    });
+    let (ws, _) = tokio_tungstenite::connect_async(format!("ws://{}", address)).await.unwrap();
+    server.await.unwrap();
+    let (_writer, reader) = ws.split();
+    let result = keep_listening_account(reader, AccountFeeds::default(), Arc::new(PendingRequests::new())).await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,51 @@
+use rust_decimal::Decimal;
+use serde_json::json;
+
+use crate::models::order_strategy::{
+    AutoTrail, EntryVersion, OrderStrategyParams, OrderStrategyResult, OrderStrategyStatus, StartOrderStrategy,
+    StrategyBracket,
+};
+use crate::models::orders::{OrderAction, OrderValidationError};
+
+#[tokio::test]
+async fn test_multi_bracket_params() {
+    let action = OrderAction::Sell;
+    let params = OrderStrategyParams::new(EntryVersion::market(2))
+        .bracket(StrategyBracket::new(&action, 1, Decimal::new(4, 0), Decimal::new(2, 0)).unwrap())
+        .bracket(
+            StrategyBracket::new(&action, 1, Decimal::new(8, 0), Decimal::new(2, 0))
+                .unwrap()
+                .auto_trail(AutoTrail {
+                    stop_loss: Decimal::new(2, 0),
+                    trigger: Decimal::new(4, 0),
+                    freq: Decimal::new(25, 2),
+                }),
+        );
+    let start = StartOrderStrategy::multi_bracket("user", 7, "ESZ2", action, &params);
+    let body = json!(start);
+    assert_eq!(body["orderStrategyTypeId"], 2);
+    assert_eq!(body["action"], "Sell");
+    let sent = serde_json::from_str::<serde_json::Value>(body["params"].as_str().unwrap()).unwrap();
+    assert_eq!(sent["entryVersion"], json!({"orderQty": 2, "orderType": "Market"}));
+    assert_eq!(sent["brackets"][0], json!({"qty": 1, "profitTarget": "-4", "stopLoss": "2", "trailingStop": false}));
+    assert_eq!(sent["brackets"][1]["autoTrail"], json!({"stopLoss": "2", "trigger": "4", "freq": "0.25"}));
+}
+
+#[tokio::test]
+async fn test_order_strategy_result() {
+    let response = r#"{"orderStrategy":{"id":11,"accountId":7,"timestamp":"2022-09-15T00:00:58.230Z","contractId":2665267,"orderStrategyTypeId":2,"action":"Buy","params":"{\"entryVersion\":{\"orderQty\":1,\"orderType\":\"Market\"},\"brackets\":[{\"qty\":1,\"profitTarget\":4.0,\"stopLoss\":-2.0,\"trailingStop\":false}]}","status":"ActiveStrategy"}}"#;
+    let result = serde_json::from_str::<OrderStrategyResult>(response).unwrap();
+    assert!(result.is_ok());
+    let strategy = result.order_strategy.unwrap();
+    assert_eq!(strategy.status, OrderStrategyStatus::ActiveStrategy);
+    assert_eq!(strategy.params().unwrap().brackets[0].profit_target, Some(Decimal::new(4, 0)));
+    let unknown = serde_json::from_str::<OrderStrategyResult>(r#"{"orderStrategy":{"id":12,"status":"SomethingNew"}}"#).unwrap();
+    assert_eq!(unknown.order_strategy.unwrap().status, OrderStrategyStatus::Unknown);
+    assert!(!OrderStrategyStatus::default().is_active());
+}
+
+#[tokio::test]
+async fn test_bracket_needs_a_side() {
+    let bracket = StrategyBracket::new(&OrderAction::Dont, 1, Decimal::new(4, 0), Decimal::new(2, 0));
+    assert_eq!(bracket, Err(OrderValidationError::MissingAction));
+}
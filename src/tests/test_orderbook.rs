@@ -0,0 +1,54 @@
+use rust_decimal::Decimal;
+
+use crate::models::orderbook::{BookSide, Depth, LiveOrderBook, OrderBook};
+use crate::websocket::feeds::MarketDataFeeds;
+use crate::websocket::process_message::parse_messages;
+use crate::websocket::rpc::PendingRequests;
+
+const DOM: &str = r#"a[{"e":"md","d":{"doms":[{"contractId":2665267,"timestamp":"2022-09-15T00:00:58.230Z","bids":[{"price":3956.25,"size":5},{"price":3956.5,"size":10},{"price":3956.0,"size":7}],"offers":[{"price":3957.0,"size":4},{"price":3956.75,"size":2}]}]}}]"#;
+const DOM_UPDATE: &str = r#"a[{"e":"md","d":{"doms":[{"contractId":2665267,"timestamp":"2022-09-15T00:00:59.230Z","bids":[{"price":3956.5,"size":0},{"price":3956.25,"size":8}],"offers":[{"price":3956.75,"size":6},{"price":3957.25,"size":3}]}]}}]"#;
+
+#[tokio::test]
+async fn test_live_orderbook() {
+    let feeds = MarketDataFeeds::default();
+    let pending = PendingRequests::new();
+    let mut books = feeds.books.subscribe_key(2665267);
+    parse_messages(DOM.to_string(), &feeds, &pending).unwrap();
+    let book = books.recv().await.unwrap();
+    assert_eq!(book.best_bid(), Some(Depth { price: Decimal::new(39565, 1), size: 10 }));
+    assert_eq!(book.best_ask(), Some(Depth { price: Decimal::new(395675, 2), size: 2 }));
+    assert_eq!(book.spread(), Some(Decimal::new(25, 2)));
+    assert_eq!(book.cumulative_size(BookSide::Bid, 2), 15);
+    parse_messages(DOM_UPDATE.to_string(), &feeds, &pending).unwrap();
+    let book = books.recv().await.unwrap();
+    assert_eq!(book.best_bid(), Some(Depth { price: Decimal::new(395625, 2), size: 8 }));
+    assert_eq!(
+        book.depth(BookSide::Ask, 5).iter().map(|d| d.size).collect::<Vec<_>>(),
+        vec![6, 3]
+    );
+    assert_eq!(book.cumulative_size(BookSide::Bid, 10), 8);
+    assert_eq!(book.imbalance(10), Some(Decimal::new(8 - 9, 0) / Decimal::new(17, 0)));
+    assert_eq!(feeds.last_books.get(2665267), Some(book));
+}
+
+#[tokio::test]
+async fn test_levels_leaving_the_snapshot_are_dropped() {
+    let mut book = LiveOrderBook::new(1);
+    let level = |price: i64, size: i64| Depth { price: Decimal::new(price, 2), size };
+    book.apply(&OrderBook {
+        contract_id: 1,
+        bids: vec![level(100, 1), level(99, 2)],
+        asks: vec![level(101, 3), level(102, 4)],
+        ..Default::default()
+    });
+    // the market moves up, 99 falls out of the top levels and the old asks are now below the bid
+    book.apply(&OrderBook {
+        contract_id: 1,
+        bids: vec![level(103, 5), level(102, 6)],
+        asks: vec![level(104, 7), level(105, 8)],
+        ..Default::default()
+    });
+    assert_eq!(book.depth(BookSide::Bid, 10), vec![level(103, 5), level(102, 6)]);
+    assert_eq!(book.depth(BookSide::Ask, 10), vec![level(104, 7), level(105, 8)]);
+    assert_eq!(book.spread(), Some(Decimal::new(1, 2)));
+}
@@ -0,0 +1,197 @@
+use rust_decimal::Decimal;
+use serde_json::json;
+
+use crate::client::{Server, TradovateClient};
+use crate::error::Error;
+use crate::tests::test_client_builder::{credentials, serve_http};
+use crate::token_store::NoTokenStore;
+use crate::models::orders::{CancelOrder, CommandResponse, CommandResult, OrderTicket, LiquidatePosition, ModifyOrder, OrderType, PlaceOrderResult, TimeInForce};
+
+#[tokio::test]
+async fn test_order_command_payloads() {
+    let modify = ModifyOrder::new(42, 2, OrderType::Limit)
+        .price(Decimal::new(395625, 2))
+        .time_in_force(TimeInForce::Day);
+    assert_eq!(
+        json!(modify),
+        json!({"orderId": 42, "orderQty": 2, "orderType": "Limit", "price": "3956.25", "timeInForce": "Day", "isAutomated": true})
+    );
+    assert_eq!(json!(CancelOrder::new(42)), json!({"orderId": 42, "isAutomated": true}));
+    assert_eq!(
+        json!(LiquidatePosition::new(7, 2665267)),
+        json!({"accountId": 7, "contractId": 2665267, "admin": false})
+    );
+}
+
+#[tokio::test]
+async fn test_order_command_results() {
+    let ok = serde_json::from_str::<CommandResult>(r#"{"commandId":123}"#).unwrap();
+    assert!(ok.is_ok());
+    let refused = serde_json::from_str::<CommandResult>(r#"{"failureReason":"UnknownReason","failureText":"Order not found"}"#).unwrap();
+    assert!(!refused.is_ok());
+    let liquidated = serde_json::from_str::<PlaceOrderResult>(r#"{"orderId":456}"#).unwrap();
+    assert_eq!(liquidated.order_id, Some(456));
+}
+
+#[tokio::test]
+async fn test_oso_brackets() {
+    use crate::models::orders::{OrderAction, OrderTicket, OrderValidationError, OsoOrder, OsoResult};
+    let entry = OrderTicket {
+        order_type: OrderType::Limit,
+        price: Some(Decimal::new(3950, 0)),
+        ..OrderTicket::market_buy("user", 7, "ESZ2", 1)
+    };
+    let oso = OsoOrder::new(entry.clone())
+        .take_profit(Decimal::new(3960, 0))
+        .stop_loss(Decimal::new(3945, 0));
+    assert_eq!(oso.validate(), Ok(()));
+    let body = json!(oso);
+    assert_eq!(body["symbol"], "ESZ2");
+    assert_eq!(body["bracket1"], json!({"action": "Sell", "orderType": "Limit", "price": "3960"}));
+    assert_eq!(body["bracket2"], json!({"action": "Sell", "orderType": "Stop", "stopPrice": "3945"}));
+    assert!(body.get("referencePrice").is_none());
+
+    let inverted = OsoOrder::new(entry.clone()).stop_loss(Decimal::new(3955, 0));
+    assert!(matches!(inverted.validate(), Err(OrderValidationError::WrongSide { .. })));
+    assert_eq!(OsoOrder::new(entry).validate(), Err(OrderValidationError::MissingBracket));
+
+    let unpriced = OsoOrder::new(OrderTicket::market_sell("user", 7, "ESZ2", 1)).take_profit(Decimal::new(3960, 0));
+    assert_eq!(unpriced.validate(), Err(OrderValidationError::MissingReferencePrice));
+    let market = unpriced.reference_price(Decimal::new(3950, 0));
+    assert!(matches!(market.validate(), Err(OrderValidationError::WrongSide { .. })));
+    assert_eq!(market.bracket1.unwrap().action, OrderAction::Buy);
+
+    let result = serde_json::from_str::<OsoResult>(r#"{"orderId":1,"oso1Id":2,"oso2Id":3}"#).unwrap();
+    assert_eq!((result.order_id, result.oso1_id, result.oso2_id), (Some(1), Some(2), Some(3)));
+}
+
+#[tokio::test]
+async fn test_oco_exit() {
+    use crate::models::orders::{BracketLeg, OcoOrder, OrderAction, OrderValidationError};
+    let exit = OcoOrder::exit("user", 7, "ESZ2", OrderAction::Sell, 1, Decimal::new(3960, 0), Decimal::new(3945, 0));
+    assert_eq!(exit.validate(), Ok(()));
+    assert_eq!(json!(exit)["other"], json!({"action": "Sell", "orderType": "Stop", "stopPrice": "3945"}));
+    let crossed = OcoOrder::exit("user", 7, "ESZ2", OrderAction::Buy, 1, Decimal::new(3960, 0), Decimal::new(3945, 0));
+    assert!(matches!(crossed.validate(), Err(OrderValidationError::WrongSide { .. })));
+
+    let touched = OcoOrder::new(
+        OrderTicket {
+            order_type: OrderType::Mit,
+            stop_price: Some(Decimal::new(3960, 0)),
+            ..OrderTicket::market_sell("user", 7, "ESZ2", 1)
+        },
+        exit.other.clone(),
+    );
+    assert_eq!(touched.validate(), Ok(()));
+    let stop_limit = BracketLeg {
+        order_type: OrderType::StopLimit,
+        price: Some(Decimal::new(3965, 0)),
+        stop_price: Some(Decimal::new(3965, 0)),
+        ..BracketLeg::stop(OrderAction::Sell, Decimal::new(3965, 0))
+    };
+    let crossed = OcoOrder::new(touched.order.clone(), stop_limit);
+    assert!(matches!(crossed.validate(), Err(OrderValidationError::WrongSide { .. })));
+}
+
+#[tokio::test]
+async fn test_mit_brackets() {
+    use crate::models::orders::{BracketLeg, OrderAction, OrderTicket, OrderValidationError, OsoOrder};
+    let entry = OrderTicket {
+        order_type: OrderType::Limit,
+        price: Some(Decimal::new(3950, 0)),
+        ..OrderTicket::market_buy("user", 7, "ESZ2", 1)
+    };
+    let touched = |stop_price| BracketLeg {
+        order_type: OrderType::Mit,
+        ..BracketLeg::stop(OrderAction::Sell, stop_price)
+    };
+    let oso = OsoOrder::new(entry.clone())
+        .bracket(touched(Decimal::new(3960, 0)))
+        .stop_loss(Decimal::new(3945, 0));
+    assert_eq!(oso.validate(), Ok(()));
+    let below = OsoOrder::new(entry).bracket(touched(Decimal::new(3940, 0)));
+    match below.validate() {
+        Err(OrderValidationError::WrongSide { order_type, .. }) => assert_eq!(order_type, OrderType::Mit),
+        other => panic!("expected a target on the wrong side, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_order_ticket_builder() {
+    use crate::models::orders::{OrderTicket, OrderValidationError};
+    use crate::models::product::Product;
+    let product = Product {
+        tick_size: Decimal::new(25, 2),
+        ..Default::default()
+    };
+    let ticket = OrderTicket::builder("user", 7, "ESZ2")
+        .buy(1)
+        .stop_limit(Decimal::new(395610, 2), Decimal::new(39562, 1))
+        .round_to(&product)
+        .build()
+        .unwrap();
+    assert_eq!(ticket.stop_price, Some(Decimal::new(395600, 2)));
+    assert_eq!(ticket.price, Some(Decimal::new(395625, 2)));
+    assert_eq!(json!(ticket)["orderType"], "StopLimit");
+    let unknown_tick = OrderTicket::builder("user", 7, "ESZ2")
+        .buy(1)
+        .limit(Decimal::new(3956, 0))
+        .round_to(&Product::default())
+        .build();
+    assert_eq!(unknown_tick, Err(OrderValidationError::InvalidTickSize(Decimal::ZERO)));
+
+    let no_action = OrderTicket::builder("user", 7, "ESZ2").market().build();
+    assert_eq!(no_action, Err(OrderValidationError::MissingAction));
+    let no_price = OrderTicket::builder("user", 7, "ESZ2").sell(1).order_type(OrderType::Limit).build();
+    assert_eq!(no_price, Err(OrderValidationError::MissingPrice { order_type: OrderType::Limit }));
+    let no_expiry = OrderTicket::builder("user", 7, "ESZ2").sell(1).time_in_force(TimeInForce::Gtd).build();
+    assert_eq!(no_expiry, Err(OrderValidationError::MissingExpireTime));
+    let gtd = OrderTicket::builder("user", 7, "ESZ2")
+        .sell(1)
+        .good_till(chrono::DateTime::parse_from_rfc3339("2022-09-15T20:00:00Z").unwrap().into())
+        .build()
+        .unwrap();
+    assert_eq!(json!(gtd)["timeInForce"], "GTD");
+    assert_eq!(gtd.expire_time.as_deref(), Some("2022-09-15T20:00:00.000Z"));
+    assert_eq!(json!(OrderType::Mit), json!("MIT"));
+}
+
+#[tokio::test]
+async fn test_rejected_commands_are_errors_over_rest() {
+    let rejected = CommandResult {
+        failure_reason: Some("UnknownReason".to_string()),
+        failure_text: Some("Order is not working".to_string()),
+        ..Default::default()
+    };
+    assert!(matches!(
+        rejected.check(),
+        Err(Error::OrderRejected { reason, text: Some(_) }) if reason == "UnknownReason"
+    ));
+    assert!(CommandResult { command_id: Some(9), ..Default::default() }.check().is_ok());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = tokio::spawn(serve_http(
+        listener,
+        vec![
+            (200, r#"{"failureReason":"UnknownReason","failureText":"Order is not working"}"#),
+            (200, r#"{"orderId":55}"#),
+        ],
+    ));
+    let client = TradovateClient::builder(Server::Demo)
+        .credentials(credentials())
+        .base_url(&format!("http://{}", address))
+        .token_store(NoTokenStore)
+        .build()
+        .unwrap();
+    assert!(matches!(
+        client.cancel_order(CancelOrder::new(42)).await,
+        Err(Error::OrderRejected { .. })
+    ));
+    let placed = client
+        .place_order(OrderTicket::market_buy("user", 7, "ESZ2", 1))
+        .await
+        .unwrap();
+    assert_eq!(placed.order_id, Some(55));
+    server.await.unwrap();
+}
@@ -0,0 +1,37 @@
+use rust_decimal::Decimal;
+
+use crate::websocket::feeds::MarketDataFeeds;
+use crate::websocket::process_message::parse_messages;
+use crate::websocket::rpc::PendingRequests;
+
+const FULL_QUOTE: &str = r#"a[{"e":"md","d":{"quotes":[{"timestamp":"2022-09-15T00:00:58.230Z","contractId":2665267,"entries":{"Bid":{"price":3956.5,"size":12},"Offer":{"price":3956.75,"size":9},"Trade":{"price":3956.75,"size":1},"TotalTradeVolume":{"size":1024},"HighPrice":{"price":3960.0}},"id":2665267}]}}]"#;
+const PARTIAL_QUOTE: &str = r#"a[{"e":"md","d":{"quotes":[{"timestamp":"2022-09-15T00:00:59.001Z","contractId":2665267,"entries":{"Bid":{"price":3956.25,"size":40}},"id":2665267}]}}]"#;
+
+#[tokio::test]
+async fn test_live_quotes_are_merged() {
+    let feeds = MarketDataFeeds::default();
+    let pending = PendingRequests::new();
+    let mut quotes = feeds.quotes.subscribe_key(2665267);
+    parse_messages(FULL_QUOTE.to_string(), &feeds, &pending).unwrap();
+    parse_messages(PARTIAL_QUOTE.to_string(), &feeds, &pending).unwrap();
+    let first = quotes.recv().await.unwrap();
+    assert_eq!(first.spread(), Some(Decimal::new(25, 2)));
+    let merged = quotes.recv().await.unwrap();
+    assert_eq!(merged.entries.bid.as_ref().unwrap().size, 40);
+    assert_eq!(merged.entries.offer.as_ref().unwrap().price, Decimal::new(395675, 2));
+    assert_eq!(merged.entries.total_trade_volume.as_ref().unwrap().size, 1024);
+    assert_eq!(merged.spread(), Some(Decimal::new(5, 1)));
+    assert_eq!(feeds.last_quotes.get(2665267), Some(merged));
+    assert!(feeds.last_quotes.get(1).is_none());
+}
+
+#[tokio::test]
+async fn test_bad_events_are_skipped() {
+    // a quote that does not parse and an unknown kind of data, followed by a good quote in the same frame
+    const MIXED: &str = r#"a[{"e":"md","d":{"quotes":"not a list"}},{"e":"md","d":{"somethingNew":[]}},{"e":"md","d":{"quotes":[{"timestamp":"2022-09-15T00:00:59.001Z","contractId":2665267,"entries":{"Bid":{"price":3956.25,"size":40}},"id":2665267}]}}]"#;
+    let feeds = MarketDataFeeds::default();
+    let pending = PendingRequests::new();
+    let mut quotes = feeds.quotes.subscribe_key(2665267);
+    parse_messages(MIXED.to_string(), &feeds, &pending).unwrap();
+    assert_eq!(quotes.recv().await.unwrap().entries.bid.unwrap().size, 40);
+}
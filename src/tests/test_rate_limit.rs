@@ -0,0 +1,79 @@
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+
+use crate::client::attach_penalty_ticket;
+use crate::rest::endpoints::{EndpointClass, ACCESS_TOKEN, ACCESS_TOKEN_RENEW, CANCEL_ORDER, ORDER_LIST, PLACE_ORDER, START_ORDER_STRATEGY};
+use crate::rest::rate_limit::{BucketLimit, RateLimiter, RateLimits, TokenBucket};
+
+#[tokio::test]
+async fn test_endpoint_classes() {
+    assert_eq!(ACCESS_TOKEN.class(), EndpointClass::Auth);
+    assert_eq!(ACCESS_TOKEN_RENEW.class(), EndpointClass::Auth);
+    assert_eq!(PLACE_ORDER.class(), EndpointClass::Order);
+    assert_eq!(CANCEL_ORDER.class(), EndpointClass::Order);
+    assert_eq!(START_ORDER_STRATEGY.class(), EndpointClass::Order);
+    assert_eq!(ORDER_LIST.class(), EndpointClass::Query);
+}
+
+#[tokio::test]
+async fn test_token_bucket_refills() {
+    let start = Instant::now();
+    let mut bucket = TokenBucket::new(BucketLimit::new(2, Duration::from_secs(1)), start);
+    assert!(bucket.try_acquire(start).is_ok());
+    assert!(bucket.try_acquire(start).is_ok());
+    let wait = bucket.try_acquire(start).unwrap_err();
+    assert_eq!(wait, Duration::from_millis(500));
+    assert!(bucket.try_acquire(start + Duration::from_millis(250)).is_err());
+    assert!(bucket.try_acquire(start + Duration::from_millis(500)).is_ok());
+    // never holds more than its capacity
+    let later = start + Duration::from_secs(60);
+    assert!(bucket.try_acquire(later).is_ok());
+    assert!(bucket.try_acquire(later).is_ok());
+    assert!(bucket.try_acquire(later).is_err());
+}
+
+#[tokio::test]
+async fn test_zero_limits_do_not_limit() {
+    let start = Instant::now();
+    for limit in [
+        BucketLimit::new(0, Duration::from_secs(1)),
+        BucketLimit::new(2, Duration::ZERO),
+        BucketLimit::new(0, Duration::ZERO),
+        BucketLimit { capacity: 0.5, per_second: 1.0 },
+    ] {
+        assert!(limit.is_unlimited());
+        let mut bucket = TokenBucket::new(limit, start);
+        for _ in 0..10 {
+            assert!(bucket.try_acquire(start).is_ok());
+        }
+    }
+    assert!(!BucketLimit::new(1, Duration::from_secs(1)).is_unlimited());
+}
+
+#[tokio::test]
+async fn test_rate_limiter_buckets_per_class() {
+    let limiter = RateLimiter::new(RateLimits {
+        auth: BucketLimit::new(1, Duration::from_secs(60)),
+        ..Default::default()
+    });
+    assert!(limiter.try_acquire(&ACCESS_TOKEN).is_ok());
+    assert!(limiter.try_acquire(&ACCESS_TOKEN_RENEW).is_err());
+    assert!(limiter.try_acquire(&ORDER_LIST).is_ok());
+    assert!(limiter.clone().try_acquire(&ACCESS_TOKEN).is_err());
+}
+
+#[tokio::test]
+async fn test_penalty_ticket_attached() {
+    let mut params = None;
+    let mut body = Some(json!({"name": "user"}));
+    attach_penalty_ticket(&mut params, &mut body, "abc");
+    assert_eq!(params, None);
+    assert_eq!(body, Some(json!({"name": "user", "p-ticket": "abc"})));
+
+    let mut params = Some(json!({"id": 1}));
+    let mut body = None;
+    attach_penalty_ticket(&mut params, &mut body, "abc");
+    assert_eq!(params, Some(json!({"id": 1, "p-ticket": "abc"})));
+    assert_eq!(body, None);
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use crate::models::shutdown::{ShutdownMessage, ShutdownReasonCode};
+use crate::websocket::reconnect::ReconnectPolicy;
+
+#[tokio::test]
+async fn test_reconnect_backoff() {
+    let policy = ReconnectPolicy {
+        initial_delay: Duration::from_secs(1),
+        max_delay: Duration::from_secs(10),
+        max_attempts: Some(5),
+    };
+    assert_eq!(policy.delay(1), Duration::from_secs(1));
+    assert_eq!(policy.delay(2), Duration::from_secs(2));
+    assert_eq!(policy.delay(4), Duration::from_secs(8));
+    assert_eq!(policy.delay(5), Duration::from_secs(10));
+    assert_eq!(policy.delay(200), Duration::from_secs(10));
+    assert!(policy.allows(5));
+    assert!(!policy.allows(6));
+    assert!(ReconnectPolicy::default().allows(u32::MAX));
+}
+
+#[tokio::test]
+async fn test_shutdown_reason_codes() {
+    let shutdown = serde_json::from_str::<ShutdownMessage>(r#"{"reasonCode":"ConnectionQuotaReached","reason":"Too many connections"}"#).unwrap();
+    assert_eq!(shutdown.reason_code, ShutdownReasonCode::ConnectionQuotaReached);
+    assert!(!shutdown.reason_code.should_reconnect());
+    let shutdown = serde_json::from_str::<ShutdownMessage>(r#"{"reasonCode":"Maintenance"}"#).unwrap();
+    assert!(shutdown.reason_code.should_reconnect());
+    let shutdown = serde_json::from_str::<ShutdownMessage>(r#"{"reasonCode":"SomethingNew"}"#).unwrap();
+    assert_eq!(shutdown.reason_code, ShutdownReasonCode::Unknown);
+}
+
+#[tokio::test]
+async fn test_rejected_authorize_is_a_failed_attempt() {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use futures::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::Message;
+    use crate::client::{Server, TradovateClient};
+    use crate::models::access_token::AccessTokenInfo;
+    use crate::tests::test_client_builder::credentials;
+    use crate::token_store::NoTokenStore;
+    use crate::websocket::feeds::MarketDataFeeds;
+    use crate::websocket::reconnect::ConnectionState;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let connections = Arc::new(AtomicU32::new(0));
+    let server_connections = connections.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            server_connections.fetch_add(1, Ordering::SeqCst);
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            ws.send(Message::Text("o".to_string())).await.unwrap();
+            if let Some(Ok(Message::Text(frame))) = ws.next().await {
+                let request_id = frame.lines().nth(1).unwrap().to_string();
+                let reply = format!(r#"a[{{"s":401,"i":{},"d":"Access is denied"}}]"#, request_id);
+                ws.send(Message::Text(reply)).await.unwrap();
+            }
+        }
+    });
+    let client = TradovateClient::builder(Server::Demo)
+        .credentials(credentials())
+        .base_url(&format!("http://{}", address))
+        .token_store(NoTokenStore)
+        .build()
+        .unwrap();
+    client.token.set(AccessTokenInfo {
+        md_access_token: "md".to_string(),
+        has_market_data: true,
+        expiration_time: (chrono::Utc::now() + chrono::Duration::hours(1)).fixed_offset(),
+        ..Default::default()
+    });
+    let policy = ReconnectPolicy {
+        initial_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(5),
+        max_attempts: Some(3),
+    };
+    let handle = client.spawn_market_data_socket(&[], MarketDataFeeds::default(), policy);
+    let result = tokio::time::timeout(Duration::from_secs(10), handle.task)
+        .await
+        .expect("the supervisor should give up instead of resetting its attempts");
+    assert!(matches!(result.unwrap(), Err(crate::error::Error::ConnectionClosed)));
+    assert!(matches!(*handle.state.borrow(), ConnectionState::Stopped { .. }));
+    assert_eq!(connections.load(Ordering::SeqCst), 4);
+}
@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::client::{Server, TradovateClient};
+use crate::error::Error;
+use crate::rest::endpoints::{CANCEL_ORDER, ORDER_LIST, PLACE_ORDER};
+use crate::rest::retry::{is_idempotent, RetryPolicy};
+
+#[tokio::test]
+async fn test_only_idempotent_requests_retried() {
+    assert!(is_idempotent(&ORDER_LIST, None));
+    assert!(!is_idempotent(&CANCEL_ORDER, Some(&json!({"orderId": 1}))));
+    assert!(!is_idempotent(&PLACE_ORDER, Some(&json!({"symbol": "ESM3"}))));
+    assert!(!is_idempotent(&PLACE_ORDER, Some(&json!({"symbol": "ESM3", "clOrdId": ""}))));
+    assert!(is_idempotent(&PLACE_ORDER, Some(&json!({"symbol": "ESM3", "clOrdId": "entry-1"}))));
+
+    let policy = RetryPolicy::default();
+    assert!(policy.should_retry(0, &ORDER_LIST, None, &Error::Timeout));
+    assert!(!policy.should_retry(policy.max_retries, &ORDER_LIST, None, &Error::Timeout));
+    assert!(!policy.should_retry(0, &CANCEL_ORDER, None, &Error::Timeout));
+    assert!(!policy.should_retry(0, &ORDER_LIST, None, &Error::Auth("denied".to_string())));
+    assert!(policy.should_retry(0, &ORDER_LIST, None, &Error::Api { status: 503, message: String::new() }));
+    assert!(!policy.should_retry(0, &ORDER_LIST, None, &Error::Api { status: 404, message: String::new() }));
+    assert!(!RetryPolicy::none().should_retry(0, &ORDER_LIST, None, &Error::Timeout));
+}
+
+#[tokio::test]
+async fn test_backoff_doubles_with_jitter() {
+    let policy = RetryPolicy::new(5, Duration::from_millis(100), Duration::from_millis(500));
+    assert_eq!(policy.backoff(0), Duration::from_millis(100));
+    assert_eq!(policy.backoff(2), Duration::from_millis(400));
+    assert_eq!(policy.backoff(3), Duration::from_millis(500));
+    assert_eq!(policy.backoff(40), Duration::from_millis(500));
+    for _ in 0..100 {
+        let delay = policy.jittered_backoff(1);
+        assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+    }
+}
+
+#[tokio::test]
+async fn test_builder_and_per_call_overrides() {
+    assert!(matches!(
+        TradovateClient::builder(Server::Demo).app("app", "1.0").build(),
+        Err(Error::Config(_))
+    ));
+    let client = TradovateClient::builder(Server::Demo)
+        .app("app", "1.0")
+        .api_key(1, "secret")
+        .user("user", "password")
+        .timeout(Duration::from_secs(5))
+        .retry_policy(RetryPolicy::none())
+        .build()
+        .unwrap();
+    assert_eq!(client.request_options().timeout, Duration::from_secs(5));
+    assert_eq!(client.request_options().retry_policy, RetryPolicy::none());
+    let patient = client
+        .with_retry_policy(RetryPolicy::default())
+        .with_timeout(Duration::from_secs(60));
+    assert_eq!(patient.request_options().retry_policy, RetryPolicy::default());
+    assert_eq!(patient.request_options().timeout, Duration::from_secs(60));
+    assert_eq!(client.request_options().timeout, Duration::from_secs(5));
+}
@@ -0,0 +1,46 @@
+use crate::websocket::rpc::{format_request, PendingRequests, WsResponse};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_format_request() {
+    let body = json!({"symbol": "ESZ2"});
+    assert_eq!(
+        format_request("md/subscribeDOM", 3, Some(&body)),
+        "md/subscribeDOM\n3\n\n{\"symbol\":\"ESZ2\"}"
+    );
+    assert_eq!(format_request("md/cancelChart", 4, None), "md/cancelChart\n4\n\n");
+}
+
+#[tokio::test]
+async fn test_pending_requests_resolve() {
+    let pending = PendingRequests::new();
+    let (first_id, _first) = pending.register();
+    let (second_id, second) = pending.register();
+    assert_eq!(first_id, 1);
+    assert_eq!(second_id, 2);
+    let response = serde_json::from_str::<WsResponse>(r#"{"s":200,"i":2,"d":{"realtimeId":9}}"#).unwrap();
+    assert!(pending.resolve(response));
+    let response = second.await.unwrap();
+    assert!(response.is_ok());
+    assert_eq!(response.data["realtimeId"], 9);
+    assert_eq!(pending.len(), 1);
+    let unknown = serde_json::from_str::<WsResponse>(r#"{"s":200,"i":0}"#).unwrap();
+    assert!(!pending.resolve(unknown));
+}
+
+#[tokio::test]
+async fn test_pending_requests_cancel_all() {
+    let pending = PendingRequests::new();
+    let (_, receiver) = pending.register();
+    pending.cancel_all();
+    assert!(receiver.await.is_err());
+    assert!(pending.is_empty());
+}
+
+#[tokio::test]
+async fn test_unmatched_error_response_keeps_the_session() {
+    let feeds = crate::websocket::feeds::MarketDataFeeds::default();
+    let pending = PendingRequests::new();
+    let late_error = r#"a[{"s":404,"i":7,"d":"Not found"}]"#.to_string();
+    assert!(crate::websocket::process_message::parse_messages(late_error, &feeds, &pending).is_ok());
+}
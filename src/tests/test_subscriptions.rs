@@ -0,0 +1,81 @@
+use crate::websocket::requests::{MarketData, MarketDataRequest};
+use crate::websocket::subscriptions::{ChartSubscriptionIds, MarketDataSubscriptions};
+
+#[tokio::test]
+async fn test_subscriptions_reference_count() {
+    let dom = MarketDataRequest::new(MarketData::DepthOfMarket, "ESZ2");
+    let quotes = MarketDataRequest::new(MarketData::Quotes, "ESZ2");
+    let subscriptions = MarketDataSubscriptions::new(&[dom.clone(), dom.clone()]);
+    assert_eq!(subscriptions.consumers(&dom).await, 2);
+    subscriptions.subscribe(quotes.clone()).await.unwrap();
+    subscriptions.subscribe(dom.clone()).await.unwrap();
+    assert_eq!(subscriptions.consumers(&dom).await, 3);
+    assert_eq!(subscriptions.active().await.len(), 2);
+    subscriptions.unsubscribe(&dom).await.unwrap();
+    subscriptions.unsubscribe(&dom).await.unwrap();
+    assert_eq!(subscriptions.consumers(&dom).await, 1);
+    subscriptions.unsubscribe(&dom).await.unwrap();
+    subscriptions.unsubscribe(&dom).await.unwrap();
+    assert_eq!(subscriptions.consumers(&dom).await, 0);
+    assert_eq!(subscriptions.active().await, vec![quotes]);
+}
+
+#[tokio::test]
+async fn test_chart_subscription_ids() {
+    let ids = serde_json::from_str::<ChartSubscriptionIds>(r#"{"historicalId":32,"realtimeId":31}"#).unwrap();
+    assert_eq!(ids.historical_id, 32);
+    let mut chart = MarketDataRequest::new(MarketData::Chart, "ESZ2");
+    chart.historical_id = ids.historical_id;
+    assert_eq!(chart.unsubscribe(5).unwrap(), "md/cancelChart\n5\n\n{\"subscriptionId\":32}");
+}
+
+#[tokio::test]
+async fn test_server_pushed_kinds_cannot_be_subscribed() {
+    let subscriptions = MarketDataSubscriptions::new(&[]);
+    for data_type in [MarketData::Clock, MarketData::Shutdown] {
+        let request = MarketDataRequest::new(data_type, "ESZ2");
+        assert!(request.subscribe(1).is_err());
+        assert!(request.unsubscribe(1).is_err());
+        assert!(subscriptions.subscribe(request.clone()).await.is_err());
+        assert_eq!(subscriptions.consumers(&request).await, 0);
+    }
+    assert!(subscriptions.active().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_subscriptions_are_not_locked_while_waiting_on_the_server() {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use futures::StreamExt;
+    use tokio::sync::Mutex;
+
+    use crate::websocket::rpc::{PendingRequests, WsRpc};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    // accepts the socket and never answers
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        while ws.next().await.is_some() {}
+    });
+    let (ws, _) = tokio_tungstenite::connect_async(format!("ws://{}", address)).await.unwrap();
+    let (writer, _reader) = ws.split();
+    let subscriptions = MarketDataSubscriptions::new(&[]);
+    subscriptions
+        .attach(WsRpc::new(Arc::new(Mutex::new(writer)), Arc::new(PendingRequests::new())))
+        .await;
+
+    let quotes = MarketDataRequest::new(MarketData::Quotes, "ESZ2");
+    let waiting = tokio::spawn({
+        let subscriptions = subscriptions.clone();
+        let quotes = quotes.clone();
+        async move { subscriptions.subscribe(quotes).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let consumers = tokio::time::timeout(Duration::from_millis(500), subscriptions.consumers(&quotes)).await;
+    assert_eq!(consumers.unwrap(), 1);
+    waiting.abort();
+    server.abort();
+}
@@ -0,0 +1,59 @@
+use crate::client::Server;
+use crate::models::access_token::AccessTokenInfo;
+use crate::token_store::{FileTokenStore, MemoryTokenStore, NoTokenStore, TokenKey, TokenStore};
+
+fn token(access_token: &str) -> AccessTokenInfo {
+    serde_json::from_value(serde_json::json!({
+        "accessToken": access_token,
+        "expirationTime": "2099-01-01T00:00:00+00:00",
+        "userId": 3
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_file_token_store() {
+    let directory = std::env::temp_dir().join(format!("tradovate_rs_token_store_{}", std::process::id()));
+    let store = FileTokenStore::new(&directory);
+    let demo = TokenKey::new(Server::Demo, "j.doe@example.com");
+    let live = TokenKey::new(Server::Live, "j.doe@example.com");
+    assert_eq!(demo.file_name(), "tradovate_auth_demo_j_doe_example_com.json");
+    assert_eq!(store.load(&demo).unwrap(), None);
+
+    store.save(&demo, &token("demo")).unwrap();
+    store.save(&live, &token("live")).unwrap();
+    store.save(&demo, &token("renewed")).unwrap();
+    assert_eq!(store.load(&demo).unwrap(), Some(token("renewed")));
+    assert_eq!(store.load(&live).unwrap(), Some(token("live")));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(store.path(&demo)).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    store.clear(&demo).unwrap();
+    assert!(!store.path(&demo).exists());
+    assert!(!store.path(&demo).with_extension("lock").exists());
+    store.clear(&demo).unwrap();
+    assert_eq!(store.load(&demo).unwrap(), None);
+    assert_eq!(store.load(&live).unwrap(), Some(token("live")));
+
+    std::fs::write(store.path(&demo), "not json").unwrap();
+    assert!(store.load(&demo).is_err());
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[tokio::test]
+async fn test_memory_and_no_token_stores() {
+    let key = TokenKey::new(Server::Demo, "user");
+    let memory = MemoryTokenStore::default();
+    memory.save(&key, &token("token")).unwrap();
+    assert_eq!(memory.clone().load(&key).unwrap(), Some(token("token")));
+    assert_eq!(memory.load(&TokenKey::new(Server::Live, "user")).unwrap(), None);
+    memory.clear(&key).unwrap();
+    assert_eq!(memory.load(&key).unwrap(), None);
+
+    NoTokenStore.save(&key, &token("token")).unwrap();
+    assert_eq!(NoTokenStore.load(&key).unwrap(), None);
+}
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{error, info, warn};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::{client::TradovateClient, models::access_token::AccessTokenInfo};
+
+/// A cloneable handle to the client's current access token.
+/// Every clone of a `TradovateClient` shares the same handle, so a renewal done by one clone
+/// is seen by all of them, and open websockets can subscribe to be told about new tokens.
+#[derive(Debug, Clone)]
+pub struct TokenHandle {
+    sender: Arc<watch::Sender<Option<AccessTokenInfo>>>,
+}
+impl Default for TokenHandle {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+impl TokenHandle {
+    pub fn new(access_token_info: Option<AccessTokenInfo>) -> Self {
+        let (sender, _) = watch::channel(access_token_info);
+        Self {
+            sender: Arc::new(sender),
+        }
+    }
+    /// Returns a copy of the current token info, if the client has authenticated.
+    pub fn current(&self) -> Option<AccessTokenInfo> {
+        self.sender.borrow().clone()
+    }
+    /// The trading token, `None` for market data only logins.
+    pub fn access_token(&self) -> Option<String> {
+        self.sender
+            .borrow()
+            .as_ref()
+            .filter(|t| t.has_trading())
+            .map(|t| t.access_token.clone())
+    }
+    /// The token renewals are sent with: the trading token, or the market data token
+    /// for market data only logins, which have no trading token.
+    pub fn renewal_token(&self) -> Option<String> {
+        self.sender.borrow().as_ref().and_then(|t| {
+            if t.has_trading() {
+                Some(t.access_token.clone())
+            } else if !t.md_access_token.is_empty() {
+                Some(t.md_access_token.clone())
+            } else {
+                None
+            }
+        })
+    }
+    pub fn is_set(&self) -> bool {
+        self.sender.borrow().is_some()
+    }
+    /// Replaces the current token and wakes every subscriber.
+    pub fn set(&self, access_token_info: AccessTokenInfo) {
+        self.sender.send_replace(Some(access_token_info));
+    }
+    /// The returned receiver is notified every time the token is replaced.
+    pub fn subscribe(&self) -> watch::Receiver<Option<AccessTokenInfo>> {
+        self.sender.subscribe()
+    }
+}
+
+/// Keeps the client's token alive by renewing it some time before `expiration_time`.
+/// If the renewal fails the manager falls back to a full credential login.
+pub struct TokenManager {
+    client: TradovateClient,
+    renew_before: Duration,
+    retry_after: Duration,
+}
+impl TokenManager {
+    pub fn new(client: TradovateClient) -> Self {
+        Self {
+            client,
+            renew_before: Duration::from_secs(15 * 60),
+            retry_after: Duration::from_secs(30),
+        }
+    }
+    /// How long before the expiration time the token is renewed. Defaults to 15 minutes.
+    pub fn renew_before(mut self, renew_before: Duration) -> Self {
+        self.renew_before = renew_before;
+        self
+    }
+    /// How long to wait before trying again after a failed renewal and login. Defaults to 30 seconds.
+    /// It is also the shortest time the manager will wait between two renewals.
+    pub fn retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = retry_after;
+        self
+    }
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(self.run())
+    }
+    pub async fn run(self) {
+        loop {
+            match self.client.token.current() {
+                Some(access_token_info) if !access_token_info.is_expired() => {
+                    let wait = access_token_info
+                        .expires_in()
+                        .checked_sub(self.renew_before)
+                        .unwrap_or_default()
+                        .max(self.retry_after);
+                    tokio::time::sleep(wait).await;
+                    match self.client.renew_access_token().await {
+                        Ok(()) => {
+                            info!("Access token renewed");
+                            continue;
+                        }
+                        Err(e) => warn!("Could not renew access token, logging in again {:?}", e),
+                    }
+                }
+                _ => warn!("Access token missing or expired, logging in again"),
+            }
+            if let Err(e) = self.client.login().await {
+                error!("Could not log in again {:?}", e);
+                tokio::time::sleep(self.retry_after).await;
+            }
+        }
+    }
+}
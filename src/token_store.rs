@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::{client::Server, error::Error, models::access_token::AccessTokenInfo};
+
+/// Who a token belongs to. Tokens of different users, or of the same user on
+/// the live and demo servers, are stored separately.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TokenKey {
+    pub server_type: Server,
+    pub username: String,
+}
+impl TokenKey {
+    pub fn new(server_type: Server, username: &str) -> Self {
+        Self {
+            server_type,
+            username: username.to_string(),
+        }
+    }
+    /// A file name safe on every platform, such as `tradovate_auth_demo_jdoe.json`.
+    pub fn file_name(&self) -> String {
+        let server = match self.server_type {
+            Server::Live => "live",
+            Server::Demo => "demo",
+        };
+        let username: String = self
+            .username
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        format!("tradovate_auth_{}_{}.json", server, username)
+    }
+}
+
+/// Where the client keeps its token between runs, so restarting does not need a new login.
+pub trait TokenStore: Debug + Send + Sync {
+    /// The saved token, expired or not, if there is one.
+    fn load(&self, key: &TokenKey) -> Result<Option<AccessTokenInfo>, Error>;
+    fn save(&self, key: &TokenKey, access_token_info: &AccessTokenInfo) -> Result<(), Error>;
+    fn clear(&self, key: &TokenKey) -> Result<(), Error>;
+}
+
+/// Saves each token to its own file in `directory`, readable only by the current user.
+/// Writes replace the file atomically and every access holds a lock on it, so processes
+/// sharing the directory never see a half written token. The lock file is removed with the token.
+/// Every call blocks while another process holds the lock, call it from a blocking thread in async code.
+#[derive(Debug, Clone)]
+pub struct FileTokenStore {
+    directory: PathBuf,
+}
+impl Default for FileTokenStore {
+    /// Saves the tokens in the working directory.
+    fn default() -> Self {
+        Self::new(".")
+    }
+}
+impl FileTokenStore {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+    pub fn path(&self, key: &TokenKey) -> PathBuf {
+        self.directory.join(key.file_name())
+    }
+    fn lock_path(&self, key: &TokenKey) -> PathBuf {
+        self.path(key).with_extension("lock")
+    }
+    /// Blocks until this process holds the lock of the token's file, released when the returned file is dropped.
+    fn lock(&self, key: &TokenKey) -> Result<File, Error> {
+        std::fs::create_dir_all(&self.directory)?;
+        let lock_path = self.lock_path(key);
+        loop {
+            let lock_file = open_private(&lock_path, false)?;
+            lock_exclusive(&lock_file)?;
+            // `clear` may have removed the file while this process waited on it
+            if is_same_file(&lock_file, &lock_path)? {
+                return Ok(lock_file);
+            }
+        }
+    }
+}
+impl TokenStore for FileTokenStore {
+    fn load(&self, key: &TokenKey) -> Result<Option<AccessTokenInfo>, Error> {
+        let _lock = self.lock(key)?;
+        match std::fs::read(self.path(key)) {
+            Ok(contents) => Ok(Some(serde_json::from_slice(&contents)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+    fn save(&self, key: &TokenKey, access_token_info: &AccessTokenInfo) -> Result<(), Error> {
+        let _lock = self.lock(key)?;
+        let path = self.path(key);
+        let temporary = path.with_extension(format!("tmp{}", std::process::id()));
+        let mut file = open_private(&temporary, true)?;
+        file.write_all(&serde_json::to_vec(access_token_info)?)?;
+        file.sync_all()?;
+        std::fs::rename(&temporary, &path)?;
+        Ok(())
+    }
+    fn clear(&self, key: &TokenKey) -> Result<(), Error> {
+        let _lock = self.lock(key)?;
+        for path in [self.path(key), self.lock_path(key)] {
+            match std::fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Opens the file for writing, creating it with 0600 permissions on unix.
+fn open_private(path: &Path, truncate: bool) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(truncate);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+/// Blocks until this process holds an exclusive lock on the file, released when it is closed.
+#[cfg(unix)]
+fn lock_exclusive(file: &File) -> std::io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+    loop {
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } == 0 {
+            return Ok(());
+        }
+        let error = std::io::Error::last_os_error();
+        if error.kind() != std::io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+}
+#[cfg(windows)]
+fn lock_exclusive(file: &File) -> std::io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::HANDLE;
+    use windows_sys::Win32::Storage::FileSystem::{LockFileEx, LOCKFILE_EXCLUSIVE_LOCK};
+    use windows_sys::Win32::System::IO::OVERLAPPED;
+    let mut overlapped: OVERLAPPED = unsafe { std::mem::zeroed() };
+    let locked = unsafe {
+        LockFileEx(
+            file.as_raw_handle() as HANDLE,
+            LOCKFILE_EXCLUSIVE_LOCK,
+            0,
+            u32::MAX,
+            u32::MAX,
+            &mut overlapped,
+        )
+    };
+    if locked == 0 {
+        Err(std::io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+#[cfg(not(any(unix, windows)))]
+fn lock_exclusive(_file: &File) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "file locks are not supported on this platform",
+    ))
+}
+
+/// True if `path` still names the open file.
+#[cfg(unix)]
+fn is_same_file(file: &File, path: &Path) -> std::io::Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+    let opened = file.metadata()?;
+    match std::fs::metadata(path) {
+        Ok(current) => Ok(opened.dev() == current.dev() && opened.ino() == current.ino()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+/// Open files can't be removed on other platforms, so the path still names it if it exists.
+#[cfg(not(unix))]
+fn is_same_file(_file: &File, path: &Path) -> std::io::Result<bool> {
+    path.try_exists()
+}
+
+/// Keeps the tokens for as long as the store lives, shared between its clones.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryTokenStore {
+    tokens: Arc<Mutex<HashMap<TokenKey, AccessTokenInfo>>>,
+}
+impl TokenStore for MemoryTokenStore {
+    fn load(&self, key: &TokenKey) -> Result<Option<AccessTokenInfo>, Error> {
+        Ok(self.tokens.lock().unwrap().get(key).cloned())
+    }
+    fn save(&self, key: &TokenKey, access_token_info: &AccessTokenInfo) -> Result<(), Error> {
+        self.tokens.lock().unwrap().insert(key.clone(), access_token_info.clone());
+        Ok(())
+    }
+    fn clear(&self, key: &TokenKey) -> Result<(), Error> {
+        self.tokens.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+/// Never keeps a token, every `authenticate` logs in with the credentials.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoTokenStore;
+impl TokenStore for NoTokenStore {
+    fn load(&self, _key: &TokenKey) -> Result<Option<AccessTokenInfo>, Error> {
+        Ok(None)
+    }
+    fn save(&self, _key: &TokenKey, _access_token_info: &AccessTokenInfo) -> Result<(), Error> {
+        Ok(())
+    }
+    fn clear(&self, _key: &TokenKey) -> Result<(), Error> {
+        Ok(())
+    }
+}
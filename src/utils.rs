@@ -1,19 +1,23 @@
 use chrono::{DateTime, FixedOffset};
 use serde::{Deserializer, Deserialize, de};
 use serde_json::Value;
+use std::path::Path;
 
-pub fn open_json(filename: &str) -> Result<Value, std::io::Error> {
+#[deprecated(note = "tokens are kept by a `TokenStore`, read other files with `serde_json` directly")]
+pub fn open_json(filename: impl AsRef<Path>) -> Result<Value, std::io::Error> {
     let file = std::fs::File::open(filename)?;
     let reader = std::io::BufReader::new(file);
     let data_file: Value = serde_json::from_reader(reader)?;
     Ok(data_file)
 }
 
-pub fn create_json_file<T: serde::Serialize>(filename: &str, contents: &T) {
+#[deprecated(note = "tokens are kept by a `TokenStore`, write other files with `serde_json` directly")]
+pub fn create_json_file<T: serde::Serialize>(filename: impl AsRef<Path>, contents: &T) {
     serde_json::to_writer(&std::fs::File::create(filename).unwrap(), contents).unwrap();
 }
 
-pub fn delete_file(filename: &str) {
+#[deprecated(note = "use `TokenStore::clear` to remove a saved token")]
+pub fn delete_file(filename: impl AsRef<Path>) {
     std::fs::remove_file(filename).unwrap()
 }
 
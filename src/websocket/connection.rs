@@ -2,7 +2,8 @@ use std::{sync::Arc};
 
 use crate::{
     client::{Protocol, ResourceType, TradovateClient},
-    models::{orderbook::OrderBooksRWL, quotes::QuotesRWL, time_and_sales::TimeAndSalesRWL},
+    error::Error,
+    models::{access_token::AccessTokenInfo, orderbook::OrderBooksRWL, quotes::QuotesRWL, time_and_sales::TimeAndSalesRWL},
     websocket::market_replay::replay_messages,
 };
 use chrono::{DateTime, Utc};
@@ -12,39 +13,38 @@ use futures::{
 };
 use log::{error, info, warn, debug};
 use serde_json::json;
-use tokio::{net::TcpStream, sync::{Notify, Mutex}};
+use tokio::{net::TcpStream, sync::{oneshot, watch, Mutex}};
 use tokio_tungstenite::{
-    tungstenite::{Error, Message},
+    tungstenite::{Error as WsError, Message},
     MaybeTlsStream, WebSocketStream,
 };
 pub type WriteWs = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
 pub type ReadWs = SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>;
+pub type SharedWriteWs = Arc<Mutex<WriteWs>>;
 use super::{
+    feeds::{AccountFeeds, MarketDataFeeds},
+    process_account::parse_account_messages,
     market_replay::MarketReplaySettings, process_message::parse_messages,
     requests::MarketDataRequest,
+    frames::{decode_frame, Event, Frame},
+    rpc::{format_request, PendingRequests, WsResponse, WsRpc},
+    subscriptions::MarketDataSubscriptions,
+    order_gateway::OrderGateway,
 };
 use crate::websocket::connection::Message::Text;
-use crate::models::user_data::UserSyncMessage;
+use crate::models::user_data::UserData;
 pub async fn keep_listening(
     mut reader: ReadWs,
-    orderbooks_rwl: OrderBooksRWL,
-    time_and_sales_rwl: TimeAndSalesRWL,
-    notify: Arc<Notify>,
+    feeds: MarketDataFeeds,
+    pending: Arc<PendingRequests>,
 ) -> Result<(), Error> {
     while let Some(msg) = reader.next().await {
         match msg {
             Ok(msg) => match msg {
                 Message::Text(txtmsg) => {
-                    if let Err(e) = parse_messages(
-                        txtmsg,
-                        orderbooks_rwl.clone(),
-                        time_and_sales_rwl.clone(),
-                        notify.clone(),
-                    )
-                    .await
-                    {
+                    if let Err(e) = parse_messages(txtmsg, &feeds, &pending) {
                         error!("Error in websocket {:#?}", e);
-                        return Err(Error::ConnectionClosed);
+                        return Err(e);
                     }
                 }
                 Message::Close(_) => {
@@ -57,29 +57,24 @@ pub async fn keep_listening(
             },
             Err(e) => {
                 error!("Error: {}", e);
-                break;
+                return Err(e.into());
             }
         }
     }
     Ok(())
 }
 
-pub async fn keep_listening_account(mut reader: ReadWs) -> Result<(), Error> {
+/// Processes the account socket messages, publishing every entity change on `feeds`.
+/// Returns `Ok` when the server closes the socket and the transport error when it drops.
+pub async fn keep_listening_account(mut reader: ReadWs, feeds: AccountFeeds, pending: Arc<PendingRequests>) -> Result<(), Error> {
     while let Some(msg) = reader.next().await {
         match msg {
             Ok(msg) => match msg {
                 Message::Text(txtmsg) => {
-                    if txtmsg.len() < 3 {
-                        continue;
-                    }
-                    let txtmsg = &txtmsg[2..txtmsg.len()-1];
-                    match serde_json::from_str::<UserSyncMessage>(&txtmsg) {
-                        Ok(acc) => {
-                            debug!("Received user sync message {:#?}",acc);
-                        }
-                        Err(_) => {
-                            error!("COuld not parse message: {}", txtmsg);
-                        }
+                    match parse_account_messages(&txtmsg, &feeds, &pending) {
+                        Ok(()) => {}
+                        Err(Error::ServerClosed { .. }) => return Ok(()),
+                        Err(e) => error!("Error in account websocket {:?}", e),
                     }
                 }
                 Message::Close(_) => {
@@ -92,93 +87,179 @@ pub async fn keep_listening_account(mut reader: ReadWs) -> Result<(), Error> {
             },
             Err(e) => {
                 error!("Error: {}", e);
-                break;
+                return Err(e.into());
             }
         }
     }
     Ok(())
 }
 
-pub async fn send_heartbeats(mut writer: WriteWs) -> Result<(), Error> {
+pub async fn send_heartbeats(writer: SharedWriteWs) -> Result<(), WsError> {
     let mut interval = tokio::time::interval(std::time::Duration::from_millis(2502));
     loop {
         interval.tick().await;
-        writer.send(Text(String::from("[]"))).await?;
+        writer.lock().await.send(Text(String::from("[]"))).await?;
     }
 }
 
-pub async fn send_heartbeats_acc(writer: Arc<Mutex<SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>>>) -> Result<(), Error> {
-    let mut interval = tokio::time::interval(std::time::Duration::from_millis(2502));
-    loop {
-        interval.tick().await;
-        writer.lock().await.send(Text(String::from("[]"))).await?;
+/// Re-sends `authorize` on the socket every time the client's token is renewed,
+/// so long running connections are not dropped when the original token expires.
+pub async fn forward_token_renewals(
+    resource_type: ResourceType,
+    mut token_receiver: watch::Receiver<Option<AccessTokenInfo>>,
+    rpc: WsRpc,
+) -> Result<(), WsError> {
+    while token_receiver.changed().await.is_ok() {
+        let auth_msg = token_receiver
+            .borrow_and_update()
+            .as_ref()
+            .map(|access_token_info| access_token_info.ws_auth_msg(resource_type, rpc.pending().next_request_id()));
+        if let Some(auth_msg) = auth_msg {
+            rpc.writer().lock().await.send(Text(auth_msg)).await?;
+            info!("Re-authorized websocket with renewed token");
+        }
+    }
+    Ok(())
+}
+
+/// Reads the socket until the response to `authorize` arrives.
+/// Other responses are handed to `pending` and data pushed before it is dropped.
+async fn wait_for_authorization(
+    reader: &mut ReadWs,
+    pending: &PendingRequests,
+    mut authorized: oneshot::Receiver<WsResponse>,
+) -> Result<(), Error> {
+    while let Some(msg) = reader.next().await {
+        let Message::Text(txtmsg) = msg? else {
+            continue;
+        };
+        match decode_frame(&txtmsg)? {
+            Frame::Array(events) => {
+                for event in events {
+                    match event {
+                        Event::Response(response) => {
+                            pending.resolve(response);
+                        }
+                        event => debug!("Dropping event received before authorization {:?}", event),
+                    }
+                }
+            }
+            Frame::Close { code, reason } => return Err(Error::ServerClosed { code, reason }),
+            Frame::Open | Frame::Heartbeat => {}
+        }
+        if let Ok(response) = authorized.try_recv() {
+            return if response.is_ok() {
+                Ok(())
+            } else {
+                Err(response.into_error())
+            };
+        }
     }
+    Err(Error::ConnectionClosed)
 }
 
 impl TradovateClient {
+    /// Opens a socket to the given resource, waits for the server to accept `authorize`
+    /// and returns an rpc handle for its writer along with the reader half.
+    /// Trading sockets are only opened for logins that can trade, see `check_trading`.
+    pub async fn open_socket(&self, resource_type: ResourceType) -> Result<(WsRpc, ReadWs), Error> {
+        if let ResourceType::Trading = resource_type {
+            self.check_trading()?;
+        }
+        let pending = Arc::new(PendingRequests::new());
+        let (request_id, authorized) = pending.register();
+        let auth_msg = self.ws_auth_msg(resource_type, request_id)?;
+        let url = self.url(resource_type, Protocol::Wss);
+        let (ws_stream, response) = tokio_tungstenite::connect_async(&url).await?;
+        info!("Connected to {url}, status {:#?}", response.status());
+        let (mut write, mut reader) = ws_stream.split();
+        write.send(Text(auth_msg)).await?;
+        let rpc = WsRpc::new(Arc::new(Mutex::new(write)), pending);
+        match tokio::time::timeout(rpc.timeout(), wait_for_authorization(&mut reader, rpc.pending(), authorized)).await {
+            Ok(Ok(())) => Ok((rpc, reader)),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(Error::Timeout),
+        }
+    }
     pub async fn connect_to_market_data_socket(
         &self,
         requests: &[MarketDataRequest],
-        orderbooks_rwl: OrderBooksRWL,
-        time_and_sales_rwl: TimeAndSalesRWL,
-        notify: Arc<Notify>,
+        feeds: MarketDataFeeds,
     ) -> Result<(), Error> {
-        let url = self.url(ResourceType::MarketData, Protocol::Wss);
-        let (ws_stream, response) = tokio_tungstenite::connect_async(url).await?;
-        info!(
-            "Connected to market data socket, status {:#?}",
-            response.status()
-        );
-        let (mut write, reader) = ws_stream.split();
-        let mut string_requests = Vec::new();
-        write.send(Text(self.ws_auth_msg())).await?;
-        for (index, request) in requests.iter().enumerate() {
-            string_requests.push(request.subscribe(index + 2));
-        }
-        for string_request in string_requests {
-            println!("{}",string_request);
-            write.send(Text(string_request)).await?;
+        let (rpc, reader) = self.open_socket(ResourceType::MarketData).await?;
+        let subscriptions = MarketDataSubscriptions::new(requests).with_tick_tape(feeds.tick_tape.clone());
+        if let Err(e) = self
+            .run_market_data_session(rpc, reader, &subscriptions, feeds)
+            .await
+        {
+            error!("Error in websocket {:#?}", e);
+            return Err(e);
         }
-        tokio::select!(
-            biased;
-            listen_result = tokio::spawn(keep_listening(reader,orderbooks_rwl,time_and_sales_rwl,notify.clone())) => {
-                if let Err(e) = listen_result.unwrap() {
-                    error!("Error in websocket {:#?}", e);
-                    return Err(Error::ConnectionClosed);
-                }
-            },
-            _ = tokio::spawn(send_heartbeats(write)) => {}
-        );
         Ok(())
     }
-    pub async fn connect_to_account_socket(&self,order_receive: tokio::sync::mpsc::Receiver<std::string::String>) -> Result<(), Error> {
-        let url = self.url(ResourceType::Trading, Protocol::Wss);
-        let (ws_stream, response) = tokio_tungstenite::connect_async(url).await?;
-        info!(
-            "Connected to market data socket, status {:#?}",
-            response.status()
-        );
-        let (mut write, reader) = ws_stream.split();
-        write.send(Text(self.ws_auth_msg())).await?;
-        write.send(Text(self.get_user_sync_request(1))).await?;
-        let sender = Arc::new(Mutex::new(write));
-        tokio::select!(
+    /// Attaches `subscriptions` to an already open market data socket and processes its messages
+    /// until the socket closes, returning the reason it did.
+    pub async fn run_market_data_session(
+        &self,
+        rpc: WsRpc,
+        reader: ReadWs,
+        subscriptions: &MarketDataSubscriptions,
+        feeds: MarketDataFeeds,
+    ) -> Result<(), Error> {
+        let token_receiver = self.token.subscribe();
+        let attach = tokio::spawn({
+            let subscriptions = subscriptions.clone();
+            let rpc = rpc.clone();
+            async move { subscriptions.attach(rpc).await }
+        });
+        let result = tokio::select!(
             biased;
-            listen_result = tokio::spawn(keep_listening_account(reader)) => {
-                if let Err(e) = listen_result.unwrap() {
-                    error!("Error in websocket {:#?}", e);
-                    return Err(Error::ConnectionClosed);
+            listen_result = keep_listening(reader,feeds,rpc.pending().clone()) => listen_result,
+            heartbeat_result = send_heartbeats(rpc.writer().clone()) => heartbeat_result.map_err(Error::from),
+            renewal_result = forward_token_renewals(ResourceType::MarketData, token_receiver, rpc.clone()) => renewal_result.map_err(Error::from)
+        );
+        attach.abort();
+        rpc.pending().cancel_all();
+        subscriptions.detach().await;
+        result
+    }
+    /// Opens the account socket, seeds `feeds.state` with the user's snapshot and then
+    /// applies and publishes every entity change. `gateway` sends its orders on this socket until it closes.
+    pub async fn connect_to_account_socket(&self,gateway: OrderGateway,feeds: AccountFeeds) -> Result<(), Error> {
+        let sync_body = self.user_sync_request_body()?;
+        let token_receiver = self.token.subscribe();
+        let (rpc, reader) = self.open_socket(ResourceType::Trading).await?;
+        let sync_rpc = rpc.clone();
+        let state = feeds.state.clone();
+        tokio::spawn(async move {
+            match sync_rpc.request_as::<UserData>("user/syncrequest", Some(sync_body)).await {
+                Ok(user_data) => {
+                    debug!("Received user sync message {:#?}", user_data);
+                    state.seed(user_data);
                 }
-            },
-            _ = tokio::spawn(send_heartbeats_acc(sender.clone())) => {
+                Err(e) => error!("User sync request failed {:?}", e),
+            }
+        });
+        gateway.attach(rpc.clone()).await;
+        let result = tokio::select!(
+            biased;
+            listen_result = keep_listening_account(reader,feeds,rpc.pending().clone()) => listen_result,
+            heartbeat_result = send_heartbeats(rpc.writer().clone()) => {
                 info!("Heartbeats stopped");
+                heartbeat_result.map_err(Error::from)
             },
-            _ =  tokio::spawn(send_orders(order_receive,sender)) => {
-
-            }
+            renewal_result = forward_token_renewals(ResourceType::Trading, token_receiver, rpc.clone()) => renewal_result.map_err(Error::from)
         );
+        gateway.detach().await;
+        rpc.pending().cancel_all();
+        if let Err(e) = result {
+            error!("Error in websocket {:#?}", e);
+            return Err(e);
+        }
         Ok(())
     }
+    /// Histograms are merged and published on `feeds`, the rest of the data is collected in the given vectors.
+    #[allow(clippy::too_many_arguments)]
     pub async fn connect_to_market_replay(
         &self,
         requests: &[MarketDataRequest],
@@ -186,40 +267,63 @@ impl TradovateClient {
         orderbooks_rwl: OrderBooksRWL,
         time_and_sales_rwl: TimeAndSalesRWL,
         quotes: QuotesRWL,
+        feeds: MarketDataFeeds,
         end_datetime: DateTime<Utc>,
     ) -> Result<(), Error> {
-        let url = self.url(ResourceType::MarketReplay, Protocol::Wss);
-        let (ws_stream, response) = tokio_tungstenite::connect_async(&url).await?;
-        info!("Connected to {url}, status {:#?}", response.status());
-        let (mut write, reader) = ws_stream.split();
-        write.send(Text(self.ws_auth_msg())).await?;
-        write.send(Text(settings.to_request(2))).await?;
-        for (index, request) in requests.iter().enumerate() {
-            let string_request = request.subscribe(index + 3);
-            write.send(Text(string_request)).await?;
-        }
+        let token_receiver = self.token.subscribe();
+        let (rpc, reader) = self.open_socket(ResourceType::MarketReplay).await?;
+        let replay_rpc = rpc.clone();
+        let settings_body = settings.to_body();
+        let requests = requests.to_vec();
+        tokio::spawn(async move {
+            match replay_rpc.request("replay/initializeclock", Some(settings_body)).await {
+                Ok(_) => send_subscriptions(replay_rpc, requests).await,
+                Err(e) => error!("Could not initialize replay clock {:?}", e),
+            }
+        });
         tokio::select!(
             biased;
-            listen_result = tokio::spawn(replay_messages(reader,orderbooks_rwl,time_and_sales_rwl,quotes,end_datetime)) => {
-                if let Err(e) = listen_result.unwrap() {
+            listen_result = tokio::spawn(replay_messages(reader,orderbooks_rwl,time_and_sales_rwl,quotes,feeds,end_datetime,rpc.pending().clone())) => {
+                rpc.pending().cancel_all();
+                let listen_result = listen_result
+                    .map_err(|e| Error::Other(format!("replay task failed: {}", e)))
+                    .and_then(|result| result);
+                if let Err(e) = listen_result {
                     error!("Error in websocket {:#?}", e);
-                    return Err(Error::ConnectionClosed);
+                    return Err(e);
                 }
             },
-            _ = tokio::spawn(send_heartbeats(write)) => {}
+            _ = tokio::spawn(send_heartbeats(rpc.writer().clone())) => {},
+            _ = tokio::spawn(forward_token_renewals(ResourceType::MarketReplay, token_receiver, rpc.clone())) => {}
         );
         Ok(())
     }
-    pub fn get_user_sync_request(&self, request_id: usize) -> String {
-        let body = json!({"users":[self.access_token_info.as_ref().unwrap().user_id]});
-        let request = format!("user/syncrequest\n{}\n\n{}", request_id, body);
-        request
+    /// Fails with `Error::Auth` before the client is authenticated.
+    pub fn user_sync_request_body(&self) -> Result<serde_json::Value, Error> {
+        let access_token_info = self
+            .token
+            .current()
+            .ok_or_else(|| Error::Auth("Cannot sync the user without an access token".to_string()))?;
+        Ok(json!({"users":[access_token_info.user_id]}))
+    }
+    pub fn get_user_sync_request(&self, request_id: usize) -> Result<String, Error> {
+        Ok(format_request("user/syncrequest", request_id, Some(&self.user_sync_request_body()?)))
     }
 }
 
-
-pub async fn send_orders(mut order_receive: tokio::sync::mpsc::Receiver<std::string::String>,sender: Arc<Mutex<SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>>>){
-    while let Some(res) = order_receive.recv().await {
-        sender.lock().await.send(Text(res)).await.unwrap();
+/// Subscribes to each request in turn, waiting for the server to acknowledge it.
+pub async fn send_subscriptions(rpc: WsRpc, requests: Vec<MarketDataRequest>) {
+    for request in requests {
+        let endpoint = match request.subscribe_endpoint() {
+            Ok(endpoint) => endpoint,
+            Err(e) => {
+                error!("Could not subscribe to {} {}", request.summarize(), e);
+                continue;
+            }
+        };
+        match rpc.request(endpoint, Some(request.subscribe_body())).await {
+            Ok(_) => info!("Subscribed to {}", request.summarize()),
+            Err(e) => error!("Could not subscribe to {} {:?}", request.summarize(), e),
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,214 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use futures::Stream;
+use log::warn;
+use tokio::sync::broadcast;
+
+use crate::models::{account_state::AccountState, entity_event::EntityEvent, histogram::{Histogram, HistogramCache}, orderbook::{LiveOrderBook, OrderBook, OrderBookCache}, quotes::{Quote, QuoteCache}, tick_chart::{Bar, ChartSummary}, time_and_sales::{TickEvent, TickTape}};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeedConfig {
+    /// How many items a slow subscriber can fall behind before it starts missing them.
+    pub buffer_size: usize,
+    /// Keep the last `history_size` items for `Feed::history`, `None` keeps nothing.
+    pub history_size: Option<usize>,
+}
+impl Default for FeedConfig {
+    fn default() -> Self {
+        Self {
+            buffer_size: 1024,
+            history_size: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedError {
+    /// The subscriber fell behind and this many items were dropped.
+    Lagged(u64),
+    /// The feed was dropped, no more items will arrive.
+    Closed,
+}
+
+struct FeedInner<T> {
+    config: FeedConfig,
+    all: broadcast::Sender<T>,
+    by_key: Mutex<HashMap<i64, broadcast::Sender<T>>>,
+    history: Mutex<VecDeque<T>>,
+}
+
+/// Broadcasts one kind of market data to every subscriber.
+/// Items are published under a key, the contract id for books and quotes and the chart
+/// subscription id for charts, so subscribers can listen to everything or to a single key.
+pub struct Feed<T> {
+    inner: Arc<FeedInner<T>>,
+}
+impl<T> Clone for Feed<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+impl<T: Clone> Feed<T> {
+    pub fn new(config: FeedConfig) -> Self {
+        let (all, _) = broadcast::channel(config.buffer_size.max(1));
+        Self {
+            inner: Arc::new(FeedInner {
+                config,
+                all,
+                by_key: Mutex::new(HashMap::new()),
+                history: Mutex::new(VecDeque::new()),
+            }),
+        }
+    }
+    pub fn publish(&self, key: i64, item: T) {
+        if let Some(history_size) = self.inner.config.history_size {
+            let mut history = self.inner.history.lock().unwrap();
+            if history.len() >= history_size {
+                history.pop_front();
+            }
+            if history_size > 0 {
+                history.push_back(item.clone());
+            }
+        }
+        let mut by_key = self.inner.by_key.lock().unwrap();
+        if let Some(sender) = by_key.get(&key) {
+            // fails once every subscriber of the key is gone
+            if sender.send(item.clone()).is_err() {
+                by_key.remove(&key);
+            }
+        }
+        drop(by_key);
+        let _ = self.inner.all.send(item);
+    }
+    /// Receives every item published from now on.
+    pub fn subscribe(&self) -> FeedReceiver<T> {
+        FeedReceiver::new(self.inner.all.subscribe())
+    }
+    /// Receives the items published under `key` from now on.
+    /// The channels of keys nobody listens to anymore are dropped.
+    pub fn subscribe_key(&self, key: i64) -> FeedReceiver<T> {
+        let mut by_key = self.inner.by_key.lock().unwrap();
+        by_key.retain(|_, sender| sender.receiver_count() > 0);
+        let sender = by_key
+            .entry(key)
+            .or_insert_with(|| broadcast::channel(self.inner.config.buffer_size.max(1)).0);
+        FeedReceiver::new(sender.subscribe())
+    }
+    /// The keys with a channel, subscribed to by `subscribe_key`.
+    pub fn subscribed_keys(&self) -> Vec<i64> {
+        let mut keys = self.inner.by_key.lock().unwrap().keys().copied().collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys
+    }
+    /// A snapshot of the bounded history, oldest first. Empty if the feed keeps no history.
+    pub fn history(&self) -> Vec<T> {
+        self.inner.history.lock().unwrap().iter().cloned().collect()
+    }
+    pub fn last(&self) -> Option<T> {
+        self.inner.history.lock().unwrap().back().cloned()
+    }
+}
+
+pub struct FeedReceiver<T> {
+    receiver: broadcast::Receiver<T>,
+    lagged: u64,
+}
+impl<T: Clone> FeedReceiver<T> {
+    fn new(receiver: broadcast::Receiver<T>) -> Self {
+        Self { receiver, lagged: 0 }
+    }
+    /// Waits for the next item. A lagging subscriber gets `FeedError::Lagged` once
+    /// and then continues with the oldest item still buffered.
+    pub async fn recv(&mut self) -> Result<T, FeedError> {
+        match self.receiver.recv().await {
+            Ok(item) => Ok(item),
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("Feed subscriber lagged, {} items dropped", skipped);
+                self.lagged += skipped;
+                Err(FeedError::Lagged(skipped))
+            }
+            Err(broadcast::error::RecvError::Closed) => Err(FeedError::Closed),
+        }
+    }
+    /// Total number of items this subscriber has missed.
+    pub fn lagged(&self) -> u64 {
+        self.lagged
+    }
+    /// Turns the receiver into a stream that ends when the feed is dropped.
+    pub fn into_stream(self) -> impl Stream<Item = Result<T, FeedError>> {
+        futures::stream::unfold(self, |mut receiver| async move {
+            match receiver.recv().await {
+                Err(FeedError::Closed) => None,
+                item => Some((item, receiver)),
+            }
+        })
+    }
+}
+
+/// The feeds the live market data socket publishes to.
+#[derive(Clone)]
+pub struct MarketDataFeeds {
+    /// The raw DOM messages.
+    pub orderbooks: Feed<OrderBook>,
+    /// Every book published is the contract's live book after applying the DOM message.
+    pub books: Feed<Arc<LiveOrderBook>>,
+    pub last_books: OrderBookCache,
+    pub time_and_sales: Feed<ChartSummary>,
+    /// Every trade of tick charts, deduplicated and in order, keyed by chart subscription id.
+    pub ticks: Feed<TickEvent>,
+    pub tick_tape: Arc<Mutex<TickTape>>,
+    /// Bars of every non tick chart, keyed by chart subscription id.
+    pub bars: Feed<Bar>,
+    /// Every quote published is the full last quote of its contract, not the partial update.
+    pub quotes: Feed<Quote>,
+    pub last_quotes: QuoteCache,
+    /// Every histogram published is the contract's merged histogram.
+    pub histograms: Feed<Histogram>,
+    pub last_histograms: HistogramCache,
+}
+impl Default for MarketDataFeeds {
+    fn default() -> Self {
+        Self::new(FeedConfig::default())
+    }
+}
+impl MarketDataFeeds {
+    pub fn new(config: FeedConfig) -> Self {
+        Self {
+            orderbooks: Feed::new(config),
+            books: Feed::new(config),
+            last_books: OrderBookCache::default(),
+            time_and_sales: Feed::new(config),
+            ticks: Feed::new(config),
+            tick_tape: Arc::new(Mutex::new(TickTape::default())),
+            bars: Feed::new(config),
+            quotes: Feed::new(config),
+            last_quotes: QuoteCache::default(),
+            histograms: Feed::new(config),
+            last_histograms: HistogramCache::default(),
+        }
+    }
+}
+
+/// What the account socket publishes to.
+#[derive(Clone)]
+pub struct AccountFeeds {
+    /// Every entity change, keyed by entity id, published once it is applied to `state`.
+    pub events: Feed<EntityEvent>,
+    pub state: AccountState,
+}
+impl Default for AccountFeeds {
+    fn default() -> Self {
+        Self::new(FeedConfig::default())
+    }
+}
+impl AccountFeeds {
+    pub fn new(config: FeedConfig) -> Self {
+        Self {
+            events: Feed::new(config),
+            state: AccountState::default(),
+        }
+    }
+}
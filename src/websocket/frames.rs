@@ -0,0 +1,66 @@
+use serde_json::Value;
+
+use super::rpc::WsResponse;
+use crate::error::Error;
+
+/// A single text frame as sent by Tradovate's SockJS style sockets.
+/// `o` opens the socket, `h` is a server heartbeat, `c[code,"reason"]` closes it
+/// and `a[...]` carries one or more events.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Frame {
+    Open,
+    Heartbeat,
+    Close { code: i64, reason: String },
+    Array(Vec<Event>),
+}
+
+/// One element of an `a[...]` frame.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// `{"s":200,"i":3,"d":{...}}`, the answer to a request made on the socket.
+    Response(WsResponse),
+    /// `{"e":"md","d":{...}}`, data pushed by the server. `name` is the `e` field.
+    Data { name: String, data: Value },
+    /// Anything else, kept so it can be logged.
+    Unknown(Value),
+}
+impl Event {
+    pub fn from_value(value: Value) -> Self {
+        let is_response = value.get("s").is_some() && value.get("i").is_some();
+        if is_response {
+            if let Ok(response) = serde_json::from_value::<WsResponse>(value.clone()) {
+                return Event::Response(response);
+            }
+        }
+        match value.get("e").and_then(Value::as_str) {
+            Some(name) => Event::Data {
+                name: name.to_string(),
+                data: value.get("d").cloned().unwrap_or_default(),
+            },
+            None => Event::Unknown(value),
+        }
+    }
+}
+
+/// A frame that is not valid json, or not one of the four frame types, is an `Error::Json` or `Error::Protocol`.
+pub fn decode_frame(raw: &str) -> Result<Frame, Error> {
+    let mut chars = raw.chars();
+    let frame_type = chars
+        .next()
+        .ok_or_else(|| Error::Protocol("empty frame".to_string()))?;
+    let payload = chars.as_str();
+    match frame_type {
+        'o' => Ok(Frame::Open),
+        'h' => Ok(Frame::Heartbeat),
+        'c' => {
+            let (code, reason) =
+                serde_json::from_str::<(i64, String)>(payload)?;
+            Ok(Frame::Close { code, reason })
+        }
+        'a' => {
+            let values = serde_json::from_str::<Vec<Value>>(payload)?;
+            Ok(Frame::Array(values.into_iter().map(Event::from_value).collect()))
+        }
+        other => Err(Error::Protocol(format!("unknown frame type {:?}", other))),
+    }
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use chrono::Utc;
 use futures::StreamExt;
 use log::error;
@@ -6,7 +8,6 @@ use log::warn;
 use serde::Serialize;
 use serde::Deserialize;
 use chrono::DateTime;
-use tokio_tungstenite::tungstenite::Error;
 use tokio_tungstenite::tungstenite::Message;
 
 
@@ -14,8 +15,12 @@ use crate::models::orderbook::OrderBooksRWL;
 use crate::models::quotes::QuotesRWL;
 use crate::models::time_and_sales::TimeAndSalesRWL;
 
+use crate::error::Error;
+
 use super::connection::ReadWs;
+use super::feeds::MarketDataFeeds;
 use super::process_replay_ms::parse_replay_messages;
+use super::rpc::PendingRequests;
 
 
 #[derive(Serialize, Deserialize, Debug,Default)]
@@ -31,6 +36,9 @@ impl MarketReplaySettings {
     pub fn to_request(&self,request_num:i64) -> String {
         format!("replay/initializeclock\n{}\n\n{}",request_num,serde_json::to_string(self).unwrap())
     }
+    pub fn to_body(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap()
+    }
 }
 
 
@@ -48,14 +56,16 @@ pub async fn replay_messages(
     orderbooks_rwl: OrderBooksRWL,
     time_and_sales_rwl: TimeAndSalesRWL,
     quotes: QuotesRWL,
+    feeds: MarketDataFeeds,
     end_time:DateTime<Utc>,
+    pending: Arc<PendingRequests>,
 ) -> Result<(), Error> {
     while let Some(msg) = reader.next().await {
         match msg {
             Ok(msg) => {
                 match msg {
                     Message::Text(txtmsg) => {
-                        match parse_replay_messages(txtmsg, orderbooks_rwl.clone(), time_and_sales_rwl.clone(),quotes.clone(),end_time).await {
+                        match parse_replay_messages(txtmsg, orderbooks_rwl.clone(), time_and_sales_rwl.clone(),quotes.clone(),&feeds,end_time,&pending).await {
                             Ok(success) => {
                                 if success {
                                     info!("Job complete");
@@ -64,7 +74,7 @@ pub async fn replay_messages(
                             },
                             Err(e) => {
                                 error!("Error parsing replay message: {:#?}", e);
-                                return Err(Error::ConnectionClosed);
+                                return Err(e);
                             }
                         }
                     },
@@ -79,7 +89,7 @@ pub async fn replay_messages(
             }
             Err(e) => {
                 error!("Error: {}", e);
-                break;
+                return Err(e.into());
             }
         }
     }
@@ -2,4 +2,11 @@ pub mod connection;
 pub mod requests;
 pub mod process_message;
 pub mod market_replay;
-pub mod process_replay_ms;
\ No newline at end of file
+pub mod process_replay_ms;
+pub mod rpc;
+pub mod frames;
+pub mod reconnect;
+pub mod subscriptions;
+pub mod feeds;
+pub mod process_account;
+pub mod order_gateway;
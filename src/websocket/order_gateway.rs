@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tokio::sync::RwLock;
+
+use crate::error::Error;
+use crate::models::orders::{
+    CancelOrder, CommandResponse, CommandResult, LiquidatePosition, ModifyOrder, OcoOrder, OcoResult, OrderTicket,
+    OsoOrder, OsoResult, PlaceOrderResult,
+};
+
+use super::rpc::WsRpc;
+
+/// Sends order commands on the account socket and waits for their acknowledgement.
+/// Every clone shares the same socket, which is attached by `connect_to_account_socket`
+/// and detached when it closes.
+#[derive(Clone, Default)]
+pub struct OrderGateway {
+    rpc: Arc<RwLock<Option<WsRpc>>>,
+}
+impl OrderGateway {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub async fn attach(&self, rpc: WsRpc) {
+        *self.rpc.write().await = Some(rpc);
+    }
+    pub async fn detach(&self) {
+        *self.rpc.write().await = None;
+    }
+    pub async fn is_connected(&self) -> bool {
+        self.rpc.read().await.is_some()
+    }
+    async fn send<T: DeserializeOwned>(&self, endpoint: &str, body: Value) -> Result<T, Error> {
+        let rpc = self.rpc.read().await.clone().ok_or(Error::ConnectionClosed)?;
+        rpc.request_as::<T>(endpoint, Some(body)).await
+    }
+    /// Places the order and returns its id.
+    pub async fn place_order(&self, order_ticket: &OrderTicket) -> Result<i64, Error> {
+        order_ticket.validate()?;
+        let result = self
+            .send::<PlaceOrderResult>("order/placeorder", json!(order_ticket))
+            .await?
+            .check()?;
+        result
+            .order_id
+            .ok_or_else(|| Error::Protocol("the acknowledgement has no order id".to_string()))
+    }
+    /// Modifies the order and returns the id of the modify command.
+    pub async fn modify_order(&self, modify_order: &ModifyOrder) -> Result<i64, Error> {
+        modify_order.validate()?;
+        let result = self
+            .send::<CommandResult>("order/modifyorder", json!(modify_order))
+            .await?
+            .check()?;
+        result
+            .command_id
+            .ok_or_else(|| Error::Protocol("the acknowledgement has no command id".to_string()))
+    }
+    /// Cancels the order and returns the id of the cancel command.
+    pub async fn cancel_order(&self, cancel_order: &CancelOrder) -> Result<i64, Error> {
+        let result = self
+            .send::<CommandResult>("order/cancelorder", json!(cancel_order))
+            .await?
+            .check()?;
+        result
+            .command_id
+            .ok_or_else(|| Error::Protocol("the acknowledgement has no command id".to_string()))
+    }
+    /// Places the entry and its brackets, the result holds the id of each of them.
+    pub async fn place_oso(&self, oso_order: &OsoOrder) -> Result<OsoResult, Error> {
+        oso_order.validate()?;
+        self.send::<OsoResult>("order/placeOSO", json!(oso_order)).await?.check()
+    }
+    pub async fn place_oco(&self, oco_order: &OcoOrder) -> Result<OcoResult, Error> {
+        oco_order.validate()?;
+        self.send::<OcoResult>("order/placeOCO", json!(oco_order)).await?.check()
+    }
+    /// Flattens the position and returns the id of the closing order.
+    pub async fn liquidate_position(&self, liquidate_position: &LiquidatePosition) -> Result<i64, Error> {
+        let result = self
+            .send::<PlaceOrderResult>("order/liquidateposition", json!(liquidate_position))
+            .await?
+            .check()?;
+        result
+            .order_id
+            .ok_or_else(|| Error::Protocol("the acknowledgement has no order id".to_string()))
+    }
+}
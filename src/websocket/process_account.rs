@@ -0,0 +1,55 @@
+use log::{debug, error, info, warn};
+
+use crate::error::Error;
+use crate::models::entity_event::EntityEvent;
+
+use super::feeds::AccountFeeds;
+use super::frames::{decode_frame, Event, Frame};
+use super::rpc::PendingRequests;
+
+/// Applies every `props` event of an account socket message to the account state and publishes it,
+/// keyed by entity id, then resolves the responses to pending requests.
+pub fn parse_account_messages(message:&str,feeds:&AccountFeeds,pending:&PendingRequests) -> Result<(),Error> {
+    match decode_frame(message) {
+        Ok(Frame::Open) => {
+            info!("account socket opened");
+            Ok(())
+        },
+        Ok(Frame::Heartbeat) => Ok(()),
+        Ok(Frame::Close { code, reason }) => {
+            warn!("server closed the socket: {} {}", code, reason);
+            Err(Error::ServerClosed { code, reason })
+        },
+        Ok(Frame::Array(frame_events)) => {
+            for event in frame_events {
+                match event {
+                    Event::Response(response) => {
+                        if !pending.resolve(response.clone()) {
+                            debug!("Received response {:?}", response);
+                        }
+                    }
+                    Event::Data { name, data } if name == "props" => {
+                        match EntityEvent::from_props(data) {
+                            Ok(event) => {
+                                feeds.state.apply(&event);
+                                feeds.events.publish(event.entity.id(), event);
+                            }
+                            Err(e) => error!("error parsing props event: {}", e),
+                        }
+                    }
+                    Event::Data { name, data } => {
+                        debug!("Received {} event {}", name, data);
+                    }
+                    Event::Unknown(value) => {
+                        error!("Could not parse message: {}", value);
+                    }
+                }
+            }
+            Ok(())
+        },
+        Err(e) => {
+            error!("error decoding frame: {:?} {}", e, message);
+            Err(e)
+        }
+    }
+}
@@ -1,110 +1,171 @@
+use serde_json::Value;
 
-
-
-use std::sync::Arc;
-
-use serde_json::{Value, Map};
-use tokio::sync::Notify;
-
-
-
-
-use crate::models::{tick_chart::ChartData, orderbook::{OrderBooks, OrderBooksRWL}, time_and_sales::TimeAndSalesRWL};
-use log::{error, warn, info};
+use crate::error::Error;
+use crate::models::{tick_chart::ChartData, histogram::Histograms, orderbook::OrderBooks, quotes::Quotes, shutdown::ShutdownMessage, time_and_sales::TickEvent};
+use log::{debug, error, warn, info};
+use super::feeds::MarketDataFeeds;
+use super::frames::{decode_frame, Event, Frame};
 use super::requests::MarketData;
+use super::rpc::PendingRequests;
 
-#[derive(Debug)]
-pub enum TradovateWSError {
-    ConnectionError,
-    ParseError(serde_json::Error),
-    UnknownError(String),
-    TooManyRetries,
+/// Merges each histogram into the contract's last one and publishes the result.
+pub fn publish_histograms(histograms: Histograms, feeds: &MarketDataFeeds) {
+    for histogram in histograms.histograms {
+        let merged = feeds.last_histograms.update(histogram);
+        feeds.histograms.publish(merged.contract_id, merged);
+    }
 }
 
-pub async fn parse_messages(message:String,orderbooks_rwl:OrderBooksRWL,time_and_sales_rwl:TimeAndSalesRWL,notify:Arc<Notify>) -> Result<(),TradovateWSError> {
-    if message.len() < 3 {
-        return Ok(())
+/// Returns the key used to pick the kind of data in a pushed event,
+/// `md` events carry it as the first key of their payload.
+pub fn event_key(name: &str, data: &Value) -> Option<String> {
+    if name == "md" {
+        data.as_object()?.keys().next().cloned()
+    } else {
+        Some(name.to_string())
     }
-    match serde_json::from_str::<Map<String,Value>>(&message[2..message.len()-1]) {
-        Ok(json_data) => {
-            println!("{:#?}", json_data);
-            if json_data.contains_key("e") {
-                let key_to_match = if json_data["e"].as_str().unwrap() == "md" {
-                    json_data["d"].clone().as_object().unwrap().keys().next().unwrap().to_string()
-                } else {
-                    json_data["e"].clone().as_str().unwrap().to_string()
-                };
-                match MarketData::from_string(&key_to_match) {
-                    Ok(data_type) => {
-                        match data_type {
-                            MarketData::DepthOfMarket => {
-                                match serde_json::from_value::<OrderBooks>(json_data["d"].clone()) {
-                                    Ok(dom_data) => {
-                                        let mut books = orderbooks_rwl.write().await;
-                                        books.push(dom_data);
-                                        Ok(())
-                                    },
-                                    Err(e) => {
-                                        error!("error parsing dom data: {}", e);
-                                        Err(TradovateWSError::ParseError(e))
+}
+
+pub fn parse_messages(message:String,feeds:&MarketDataFeeds,pending:&PendingRequests) -> Result<(),Error> {
+    match decode_frame(&message) {
+        Ok(Frame::Open) => {
+            info!("market data socket opened");
+            Ok(())
+        },
+        Ok(Frame::Heartbeat) => Ok(()),
+        Ok(Frame::Close { code, reason }) => {
+            warn!("server closed the socket: {} {}", code, reason);
+            Err(Error::ServerClosed { code, reason })
+        },
+        Ok(Frame::Array(events)) => {
+            // every event of the frame is handled, even after a shutdown
+            let mut result = Ok(());
+            for event in events {
+                if let Err(e) = parse_event(event, feeds, pending) {
+                    if result.is_ok() {
+                        result = Err(e);
+                    }
+                }
+            }
+            result
+        },
+        Err(e) => {
+            error!("error decoding frame: {:?} {}", e, message);
+            Err(e)
+        }
+    }
+}
+
+/// Only a server shutdown is an error, an event that can't be parsed is logged and skipped.
+fn parse_event(event:Event,feeds:&MarketDataFeeds,pending:&PendingRequests) -> Result<(),Error> {
+    match event {
+        Event::Data { name, data } => {
+            let key_to_match = event_key(&name, &data).unwrap_or_default();
+            match MarketData::from_string(&key_to_match) {
+                Ok(data_type) => {
+                    match data_type {
+                        MarketData::DepthOfMarket => {
+                            match serde_json::from_value::<OrderBooks>(data) {
+                                Ok(dom_data) => {
+                                    for book in dom_data.doms {
+                                        feeds.books.publish(book.contract_id, feeds.last_books.update(&book));
+                                        feeds.orderbooks.publish(book.contract_id, book);
                                     }
+                                    Ok(())
+                                },
+                                Err(e) => {
+                                    error!("error parsing dom data, skipping it: {}", e);
+                                    Ok(())
                                 }
-                            },
-                            MarketData::Quotes => {
-                                Ok(())
-                            },
-                            MarketData::Histogram => todo!(),
-                            MarketData::Chart => {
-                                match serde_json::from_value::<ChartData>(json_data["d"].clone()) {
-                                    Ok(chart_data) => {
-                                        if let Some(combined) = chart_data.combine_all_ticks() {
-                                            let mut ts = time_and_sales_rwl.write().await;
-                                            ts.push(combined);
-                                            notify.notify_one();
-                                        }
-                                        Ok(())
-                                    },
-                                    Err(e) => {
-                                        error!("error parsing chart data: {}", e);
-                                        Err(TradovateWSError::ParseError(e))
+                            }
+                        },
+                        MarketData::Quotes => {
+                            match serde_json::from_value::<Quotes>(data) {
+                                Ok(quotes) => {
+                                    for quote in quotes.quotes {
+                                        let last_quote = feeds.last_quotes.update(quote);
+                                        feeds.quotes.publish(last_quote.contract_id, last_quote);
                                     }
+                                    Ok(())
+                                },
+                                Err(e) => {
+                                    error!("error parsing quote data, skipping it: {}", e);
+                                    Ok(())
                                 }
-                            },
-                            MarketData::Shutdown => {
-                                error!("received shutdown message from server");
-                                warn!("{}", message);
-                                Err(TradovateWSError::ConnectionError)
-                            },
-                            MarketData::Clock => {
-                                info!("received clock message from server");
-                                Ok(())
-                            },
-                        }
-                    },
-                    Err(e) => {
-                        error!("error parsing market data type: {} {:#?}", e, json_data);
-                        Err(TradovateWSError::ParseError(e))
+                            }
+                        },
+                        MarketData::Histogram => {
+                            match serde_json::from_value::<Histograms>(data) {
+                                Ok(histograms) => {
+                                    publish_histograms(histograms, feeds);
+                                    Ok(())
+                                },
+                                Err(e) => {
+                                    error!("error parsing histogram data, skipping it: {}", e);
+                                    Ok(())
+                                }
+                            }
+                        },
+                        MarketData::Chart => {
+                            match serde_json::from_value::<ChartData>(data) {
+                                Ok(chart_data) => {
+                                    for bar in chart_data.get_all_bars() {
+                                        feeds.bars.publish(bar.historical_id, bar);
+                                    }
+                                    for event in feeds.tick_tape.lock().unwrap().process(&chart_data) {
+                                        let historical_id = match &event {
+                                            TickEvent::Trade(item) => item.historical_id,
+                                            TickEvent::EndOfHistory(historical_id) => *historical_id,
+                                        };
+                                        feeds.ticks.publish(historical_id, event);
+                                    }
+                                    if let Some(combined) = chart_data.combine_all_ticks() {
+                                        let subscription_id = chart_data.charts.first().map_or(0, |c| c.historical_id);
+                                        feeds.time_and_sales.publish(subscription_id, combined);
+                                    }
+                                    Ok(())
+                                },
+                                Err(e) => {
+                                    error!("error parsing chart data, skipping it: {}", e);
+                                    Ok(())
+                                }
+                            }
+                        },
+                        MarketData::Shutdown => {
+                            error!("received shutdown message from server");
+                            warn!("{}", data);
+                            match serde_json::from_value::<ShutdownMessage>(data) {
+                                Ok(shutdown) => Err(Error::Shutdown(shutdown)),
+                                Err(e) => Err(Error::Json(e)),
+                            }
+                        },
+                        MarketData::Clock => {
+                            info!("received clock message from server");
+                            Ok(())
+                        },
                     }
+                },
+                Err(e) => {
+                    error!("error parsing market data type, skipping it: {} {} {:#?}", e, name, data);
+                    Ok(())
                 }
-            } else if json_data.contains_key("s") {
-                if json_data["s"].as_i64().unwrap() == 200 {
-                    info!("successfully subscribed to market data");
-                    return Ok(())
+            }
+        },
+        Event::Response(response) => {
+            // late replies to requests that already timed out have no pending entry, they are dropped
+            if !pending.resolve(response.clone()) {
+                if response.is_ok() {
+                    debug!("received response to no pending request {:?}", response);
                 } else {
-                    error!("received error message from server");
-                    warn!("{}", message);
-                    return Err(TradovateWSError::UnknownError(message))
+                    warn!("received error response to no pending request {:?}", response);
                 }
-            } else {
-                error!("received unknown message from server");
-                warn!("{}", message);
-                return Ok(())
             }
+            Ok(())
         },
-        Err(e) => {
-            error!("error parsing message: {}", e);
-            Err(TradovateWSError::ParseError(e))
+        Event::Unknown(value) => {
+            error!("received unknown message from server");
+            warn!("{}", value);
+            Ok(())
         }
     }
 }
-
@@ -1,113 +1,142 @@
 use chrono::{DateTime, Utc};
-use log::{error, warn, info, debug};
-use serde_json::{Value, Map};
+use log::{error, warn, debug};
 
-use crate::{models::{orderbook::{OrderBooksRWL, OrderBooks}, time_and_sales::TimeAndSalesRWL, tick_chart::ChartData, quotes::{Quotes, QuotesRWL}, replay_clock::ReplayClock}, websocket::process_message::TradovateWSError};
+use crate::{models::{orderbook::{OrderBooksRWL, OrderBooks}, time_and_sales::TimeAndSalesRWL, tick_chart::ChartData, quotes::{Quotes, QuotesRWL}, replay_clock::ReplayClock, shutdown::ShutdownMessage, histogram::Histograms}, websocket::process_message::{event_key, publish_histograms}, error::Error};
 
+use super::feeds::MarketDataFeeds;
+use super::frames::{decode_frame, Event, Frame};
 use super::requests::MarketData;
+use super::rpc::PendingRequests;
 
 
 ///Returns true if the job is complete. It is configured mostly to use market replay to gather data.
-pub async fn parse_replay_messages(message:String,orderbooks_rwl:OrderBooksRWL,time_and_sales_rwl:TimeAndSalesRWL,quotes:QuotesRWL,end_time:DateTime<Utc>) -> Result<bool,TradovateWSError> {
-    if message.len() < 3 {
-        return Ok(false)
+///Histograms are merged and published on `feeds` like live ones.
+pub async fn parse_replay_messages(message:String,orderbooks_rwl:OrderBooksRWL,time_and_sales_rwl:TimeAndSalesRWL,quotes:QuotesRWL,feeds:&MarketDataFeeds,end_time:DateTime<Utc>,pending:&PendingRequests) -> Result<bool,Error> {
+    match decode_frame(&message) {
+        Ok(Frame::Open) | Ok(Frame::Heartbeat) => Ok(false),
+        Ok(Frame::Close { code, reason }) => {
+            warn!("server closed the socket: {} {}", code, reason);
+            Err(Error::ServerClosed { code, reason })
+        },
+        Ok(Frame::Array(events)) => {
+            let mut complete = false;
+            for event in events {
+                complete |= parse_replay_event(event, &orderbooks_rwl, &time_and_sales_rwl, &quotes, feeds, end_time, pending).await?;
+            }
+            Ok(complete)
+        },
+        Err(e) => {
+            error!("error decoding frame: {:?} {}", e, message);
+            Err(e)
+        }
     }
-    match serde_json::from_str::<Map<String,Value>>(&message[2..message.len()-1]) {
-        Ok(json_data) => {
-            if json_data.contains_key("e") {
-                let key_to_match = if json_data["e"].as_str().unwrap() == "md" {
-                    json_data["d"].clone().as_object().unwrap().keys().next().unwrap().to_string()
-                } else {
-                    json_data["e"].clone().as_str().unwrap().to_string()
-                };
-                match MarketData::from_string(&key_to_match) {
-                    Ok(data_type) => {
-                        match data_type {
-                            MarketData::DepthOfMarket => {
-                                match serde_json::from_value::<OrderBooks>(json_data["d"].clone()) {
-                                    Ok(dom_data) => {
-                                        let mut books = orderbooks_rwl.write().await;
-                                        books.push(dom_data);
-                                        Ok(false)
-                                    },
-                                    Err(e) => {
-                                        error!("error parsing dom data: {}", e);
-                                        Err(TradovateWSError::ParseError(e))
-                                    }
+}
+
+async fn parse_replay_event(event:Event,orderbooks_rwl:&OrderBooksRWL,time_and_sales_rwl:&TimeAndSalesRWL,quotes:&QuotesRWL,feeds:&MarketDataFeeds,end_time:DateTime<Utc>,pending:&PendingRequests) -> Result<bool,Error> {
+    match event {
+        Event::Data { name, data } => {
+            let key_to_match = event_key(&name, &data).unwrap_or_default();
+            match MarketData::from_string(&key_to_match) {
+                Ok(data_type) => {
+                    match data_type {
+                        MarketData::DepthOfMarket => {
+                            match serde_json::from_value::<OrderBooks>(data) {
+                                Ok(dom_data) => {
+                                    let mut books = orderbooks_rwl.write().await;
+                                    books.push(dom_data);
+                                    Ok(false)
+                                },
+                                Err(e) => {
+                                    error!("error parsing dom data: {}", e);
+                                    Err(Error::Json(e))
                                 }
-                            },
-                            MarketData::Quotes => {
-                                match serde_json::from_value::<Quotes>(json_data["d"].clone()) {
-                                    Ok(quote) => {
-                                        debug!("quote: {:#?}", quote);
-                                        let mut quotes = quotes.write().await;
-                                        quotes.push(quote);
-                                        Ok(false)
-                                    },
-                                    Err(e) => {
-                                        error!("error parsing dom data: {}", e);
-                                        Err(TradovateWSError::ParseError(e))
-                                    }
+                            }
+                        },
+                        MarketData::Quotes => {
+                            match serde_json::from_value::<Quotes>(data) {
+                                Ok(quote) => {
+                                    debug!("quote: {:#?}", quote);
+                                    let mut quotes = quotes.write().await;
+                                    quotes.push(quote);
+                                    Ok(false)
+                                },
+                                Err(e) => {
+                                    error!("error parsing dom data: {}", e);
+                                    Err(Error::Json(e))
                                 }
-                            },
-                            MarketData::Histogram => todo!(),
-                            MarketData::Chart => {
-                                match serde_json::from_value::<ChartData>(json_data["d"].clone()) {
-                                    Ok(chart_data) => {
-                                        debug!("chart data: {:#?}", chart_data);
-                                        if let Some(combined) = chart_data.combine_all_ticks() {
-                                            let mut ts = time_and_sales_rwl.write().await;
-                                            ts.push(combined);
-                                        }
-                                        Ok(false)
-                                    },
-                                    Err(e) => {
-                                        error!("error parsing chart data: {}", e);
-                                        Err(TradovateWSError::ParseError(e))
-                                    }
+                            }
+                        },
+                        MarketData::Histogram => {
+                            match serde_json::from_value::<Histograms>(data) {
+                                Ok(histograms) => {
+                                    debug!("histograms: {:#?}", histograms);
+                                    publish_histograms(histograms, feeds);
+                                    Ok(false)
+                                },
+                                Err(e) => {
+                                    error!("error parsing histogram data: {}", e);
+                                    Err(Error::Json(e))
                                 }
-                            },
-                            MarketData::Shutdown => {
-                                error!("received shutdown message from server");
-                                warn!("{}", message);
-                                Err(TradovateWSError::ConnectionError)
-                            },
-                            MarketData::Clock => {
-                                match serde_json::from_str::<ReplayClock>(json_data["d"].clone().as_str().unwrap()) {
-                                    Ok(p_clock) => {
-                                        Ok(p_clock.time >= end_time)
-                                    },
-                                    Err(e) => {
-                                        error!("error parsing clock data: {}", e);
-                                        Err(TradovateWSError::ParseError(e))
+                            }
+                        },
+                        MarketData::Chart => {
+                            match serde_json::from_value::<ChartData>(data) {
+                                Ok(chart_data) => {
+                                    debug!("chart data: {:#?}", chart_data);
+                                    if let Some(combined) = chart_data.combine_all_ticks() {
+                                        let mut ts = time_and_sales_rwl.write().await;
+                                        ts.push(combined);
                                     }
+                                    Ok(false)
+                                },
+                                Err(e) => {
+                                    error!("error parsing chart data: {}", e);
+                                    Err(Error::Json(e))
+                                }
+                            }
+                        },
+                        MarketData::Shutdown => {
+                            error!("received shutdown message from server");
+                            warn!("{}", data);
+                            match serde_json::from_value::<ShutdownMessage>(data) {
+                                Ok(shutdown) => Err(Error::Shutdown(shutdown)),
+                                Err(e) => Err(Error::Json(e)),
+                            }
+                        },
+                        MarketData::Clock => {
+                            match serde_json::from_str::<ReplayClock>(data.as_str().unwrap_or_default()) {
+                                Ok(p_clock) => {
+                                    Ok(p_clock.time >= end_time)
+                                },
+                                Err(e) => {
+                                    error!("error parsing clock data: {}", e);
+                                    Err(Error::Json(e))
                                 }
-                            },
-                        }
-                    },
-                    Err(e) => {
-                        error!("error parsing market data type: {}", e);
-                        Ok(false)
+                            }
+                        },
                     }
+                },
+                Err(e) => {
+                    error!("error parsing market data type: {}", e);
+                    Ok(false)
                 }
-            } else if json_data.contains_key("s") {
-                if json_data["s"].as_i64().unwrap() == 200 {
-                    info!("successfully subscribed to market data");
-                    return Ok(false)
+            }
+        },
+        Event::Response(response) => {
+            // late replies to requests that already timed out have no pending entry, they are dropped
+            if !pending.resolve(response.clone()) {
+                if response.is_ok() {
+                    debug!("received response to no pending request {:?}", response);
                 } else {
-                    error!("received error message from server");
-                    warn!("{}", message);
-                    return Err(TradovateWSError::UnknownError(message))
+                    warn!("received error response to no pending request {:?}", response);
                 }
-            } else {
-                error!("received unknown message from server");
-                warn!("{}", message);
-                return Ok(false)
             }
+            Ok(false)
         },
-        Err(e) => {
-            error!("error parsing message: {}", e);
-            Err(TradovateWSError::ParseError(e))
+        Event::Unknown(value) => {
+            error!("received unknown message from server");
+            warn!("{}", value);
+            Ok(false)
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use log::{error, info, warn};
+use tokio::sync::watch;
+use crate::client::{ResourceType, TradovateClient};
+use crate::error::Error;
+
+use super::{
+    feeds::MarketDataFeeds,
+    requests::MarketDataRequest,
+    subscriptions::{MarketDataHandle, MarketDataSubscriptions},
+};
+
+/// How a supervised connection waits between reconnection attempts.
+/// The delay doubles after every failed attempt, starting at `initial_delay` and capped at `max_delay`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    /// Give up after this many attempts in a row, `None` retries forever.
+    pub max_attempts: Option<u32>,
+}
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            max_attempts: None,
+        }
+    }
+}
+impl ReconnectPolicy {
+    /// The delay before the given attempt, attempts start at 1.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+    pub fn allows(&self, attempt: u32) -> bool {
+        self.max_attempts.is_none_or(|max_attempts| attempt <= max_attempts)
+    }
+}
+
+/// Published by supervised connections so consumers can pause while the data is not flowing.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum ConnectionState {
+    #[default]
+    Disconnected,
+    Connecting,
+    Connected,
+    Reconnecting {
+        attempt: u32,
+        delay: Duration,
+        reason: String,
+    },
+    /// The supervisor gave up and will not reconnect.
+    Stopped { reason: String },
+}
+impl ConnectionState {
+    pub fn is_connected(&self) -> bool {
+        self == &ConnectionState::Connected
+    }
+}
+
+impl TradovateClient {
+    /// Starts a supervised market data connection in the background and returns a handle
+    /// to change its subscriptions at runtime and follow its state.
+    pub fn spawn_market_data_socket(
+        &self,
+        requests: &[MarketDataRequest],
+        feeds: MarketDataFeeds,
+        policy: ReconnectPolicy,
+    ) -> MarketDataHandle {
+        let subscriptions = MarketDataSubscriptions::new(requests).with_tick_tape(feeds.tick_tape.clone());
+        let (state_sender, state) = watch::channel(ConnectionState::default());
+        let client = self.clone();
+        let task_subscriptions = subscriptions.clone();
+        let task = tokio::spawn(async move {
+            client
+                .connect_to_market_data_socket_supervised(
+                    &task_subscriptions,
+                    feeds,
+                    &policy,
+                    &state_sender,
+                )
+                .await
+        });
+        MarketDataHandle {
+            subscriptions,
+            state,
+            task,
+        }
+    }
+    /// Same as `connect_to_market_data_socket`, but reconnects with exponential backoff whenever the socket drops,
+    /// logging in again if the token expired and re-sending every active subscription.
+    /// A server `shutdown` for a reached quota stops the supervisor, since reconnecting would not help.
+    /// Every change of state is published on `state`.
+    pub async fn connect_to_market_data_socket_supervised(
+        &self,
+        subscriptions: &MarketDataSubscriptions,
+        feeds: MarketDataFeeds,
+        policy: &ReconnectPolicy,
+        state: &watch::Sender<ConnectionState>,
+    ) -> Result<(), Error> {
+        let mut attempt = 0;
+        loop {
+            state.send_replace(ConnectionState::Connecting);
+            let reason = match self.reconnect_market_data_session(
+                subscriptions,
+                feeds.clone(),
+                state,
+                &mut attempt,
+            )
+            .await
+            {
+                Err(Error::Shutdown(shutdown)) if !shutdown.reason_code.should_reconnect() => {
+                    let reason = format!("{:?} {}", shutdown.reason_code, shutdown.reason.as_deref().unwrap_or_default());
+                    error!("Market data socket shut down by the server: {}", reason);
+                    state.send_replace(ConnectionState::Stopped { reason });
+                    return Err(Error::Shutdown(shutdown));
+                }
+                Err(e) => e.to_string(),
+                Ok(()) => "socket closed".to_string(),
+            };
+            attempt += 1;
+            if !policy.allows(attempt) {
+                error!("Giving up on market data socket after {} attempts", attempt - 1);
+                state.send_replace(ConnectionState::Stopped { reason });
+                return Err(Error::ConnectionClosed);
+            }
+            let delay = policy.delay(attempt);
+            warn!("Market data socket disconnected ({}), reconnecting in {:?}", reason, delay);
+            state.send_replace(ConnectionState::Reconnecting { attempt, delay, reason });
+            tokio::time::sleep(delay).await;
+        }
+    }
+    async fn reconnect_market_data_session(
+        &self,
+        subscriptions: &MarketDataSubscriptions,
+        feeds: MarketDataFeeds,
+        state: &watch::Sender<ConnectionState>,
+        attempt: &mut u32,
+    ) -> Result<(), Error> {
+        if self.token.current().is_none_or(|token| token.is_expired()) {
+            self.authenticate().await?;
+        }
+        let (rpc, reader) = self.open_socket(ResourceType::MarketData).await?;
+        info!("Market data socket connected");
+        *attempt = 0;
+        state.send_replace(ConnectionState::Connected);
+        self.run_market_data_session(rpc, reader, subscriptions, feeds)
+            .await
+    }
+}
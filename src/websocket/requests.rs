@@ -6,6 +6,8 @@ use serde::Deserialize;
 use chrono::DateTime;
 use chrono::Utc;
 
+use super::rpc::format_request;
+
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize,Default)]
 pub enum MarketData {
     #[serde(rename(deserialize = "doms"))]
@@ -22,7 +24,7 @@ impl MarketData {
     pub fn from_string(data_type: &str) -> Result<Self,serde_json::Error> {
         match data_type {
             "doms" => Ok(MarketData::DepthOfMarket),
-            "histogram" => Ok(MarketData::Histogram),
+            "histogram" | "histograms" => Ok(MarketData::Histogram),
             "chart" => Ok(MarketData::Chart),
             "shutdown" => Ok(MarketData::Shutdown),
             "clock" => Ok(MarketData::Clock),
@@ -38,7 +40,11 @@ pub struct MarketDataRequest {
     pub symbol: String,
     pub contract_id: i64,
     pub historical_id: i64,
+    pub realtime_id: i64,
     pub start_date:Option<DateTime<Utc>>,
+    pub chart_description: ChartDescription,
+    /// Overrides `start_date` when set.
+    pub time_range: Option<TimeRange>,
 }
 impl MarketDataRequest {
     pub fn new(data_type: MarketData, symbol: &str) -> Self {
@@ -56,37 +62,56 @@ impl MarketDataRequest {
             symbol: symbol.to_string(),
             contract_id: 0,
             historical_id: 0,
+            realtime_id: 0,
             start_date: Some(date),
+            ..Default::default()
+        }
+    }
+    pub fn chart(symbol: &str, chart_description: ChartDescription, time_range: TimeRange) -> Self {
+        Self {
+            data_type: MarketData::Chart,
+            symbol: symbol.to_string(),
+            chart_description,
+            time_range: Some(time_range),
+            ..Default::default()
+        }
+    }
+    /// Shutdown and clock messages are pushed by the server, they can't be subscribed to.
+    pub fn subscribe_endpoint(&self) -> Result<&'static str, crate::error::Error> {
+        match self.data_type {
+            MarketData::Histogram => Ok("md/subscribeHistogram"),
+            MarketData::Chart => Ok("md/getChart"),
+            MarketData::DepthOfMarket => Ok("md/subscribeDOM"),
+            MarketData::Quotes => Ok("md/subscribeQuote"),
+            MarketData::Shutdown | MarketData::Clock => Err(self.not_subscribable()),
         }
     }
-    pub fn subscribe(&self, request_id: usize) -> String {
-        let endpoint = match self.data_type {
-            MarketData::Histogram => "md/subscribeHistogram",
-            MarketData::Chart => "md/getChart",
-            MarketData::Shutdown => "shutdown",
-            MarketData::DepthOfMarket => "md/subscribeDOM",
-            MarketData::Quotes => "md/subscribeQuote",
-            MarketData::Clock => todo!(),
-        };
-        let request_body = if self.data_type != MarketData::Chart {
+    fn not_subscribable(&self) -> crate::error::Error {
+        crate::error::Error::InvalidRequest(format!("{:?} messages can't be subscribed to", self.data_type))
+    }
+    pub fn subscribe_body(&self) -> Value {
+        if self.data_type != MarketData::Chart {
             json!({
                 "symbol": self.symbol
             })
         } else {
-            self.get_tick_chart_request_body()
-        };
-        format!("{}\n{}\n\n{}", endpoint, request_id, request_body)
-    }
-    pub fn unsubscribe(&self, request_id: i32) -> String {
-        let endpoint = match self.data_type {
-            MarketData::DepthOfMarket => "md/unsubscribeDOM",
-            MarketData::Quotes => "md/unsubscribeQuote",
-            MarketData::Histogram => "md/unsubscribeHistogram",
-            MarketData::Chart => "md/cancelChart",
-            MarketData::Shutdown => todo!(),
-            MarketData::Clock => todo!(),
-        };
-        let request_body = if self.data_type != MarketData::Chart {
+            self.get_chart_request_body()
+        }
+    }
+    pub fn subscribe(&self, request_id: usize) -> Result<String, crate::error::Error> {
+        Ok(format_request(self.subscribe_endpoint()?, request_id, Some(&self.subscribe_body())))
+    }
+    pub fn unsubscribe_endpoint(&self) -> Result<&'static str, crate::error::Error> {
+        match self.data_type {
+            MarketData::DepthOfMarket => Ok("md/unsubscribeDOM"),
+            MarketData::Quotes => Ok("md/unsubscribeQuote"),
+            MarketData::Histogram => Ok("md/unsubscribeHistogram"),
+            MarketData::Chart => Ok("md/cancelChart"),
+            MarketData::Shutdown | MarketData::Clock => Err(self.not_subscribable()),
+        }
+    }
+    pub fn unsubscribe_body(&self) -> Value {
+        if self.data_type != MarketData::Chart {
             json!({
                 "symbol": self.symbol
             })
@@ -94,31 +119,165 @@ impl MarketDataRequest {
             json!({
                 "subscriptionId": self.historical_id
             })
-        };
-        format!("{}\n{}\n\n{}", endpoint, request_id, request_body)
+        }
+    }
+    pub fn unsubscribe(&self, request_id: usize) -> Result<String, crate::error::Error> {
+        Ok(format_request(self.unsubscribe_endpoint()?, request_id, Some(&self.unsubscribe_body())))
     }
     pub fn summarize(&self) -> String {
         format!("{} {:?}", self.symbol, self.data_type)
     }
-    pub fn get_tick_chart_request_body(&self) -> Value {
-        let time_stamp = if let Some(date) = self.start_date {
-            date
+    /// Two requests with the same key are the same subscription on the server.
+    pub fn subscription_key(&self) -> String {
+        if self.data_type == MarketData::Chart {
+            format!(
+                "{} {:?} {:?} {:?}",
+                self.summarize(),
+                self.chart_description,
+                self.time_range,
+                self.start_date
+            )
         } else {
-            Utc::now()
-        };
-        let formatted = time_stamp.to_rfc3339();
+            self.summarize()
+        }
+    }
+    pub fn get_chart_request_body(&self) -> Value {
+        let time_range = self.time_range.clone().unwrap_or_else(|| {
+            TimeRange::as_far_as_timestamp(self.start_date.unwrap_or_else(Utc::now))
+        });
         json!({
           "symbol": self.symbol,
-          "chartDescription": {
-            "underlyingType": "Tick",
-            "elementSize": 1,
-            "elementSizeUnit": "UnderlyingUnits"
-          },
-          "timeRange": {
-              "asFarAsTimestamp": formatted,
-          }
+          "chartDescription": self.chart_description,
+          "timeRange": time_range,
         })
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Default)]
+pub enum UnderlyingType {
+    MinuteBar,
+    DailyBar,
+    #[default]
+    Tick,
+    Custom,
+    #[serde(rename = "DOM")]
+    Dom,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Default)]
+pub enum ElementSizeUnit {
+    #[default]
+    UnderlyingUnits,
+    Volume,
+    Range,
+    Renko,
+    MomentumRange,
+    PointAndFigure,
+    #[serde(rename = "OFARange")]
+    OfaRange,
+}
 
+/// What each element of a chart is made of.
+/// The default is the raw tick stream, every other description makes the server send bars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartDescription {
+    pub underlying_type: UnderlyingType,
+    pub element_size: i64,
+    pub element_size_unit: ElementSizeUnit,
+    pub with_histogram: bool,
+}
+impl Default for ChartDescription {
+    fn default() -> Self {
+        Self::ticks()
+    }
+}
+impl ChartDescription {
+    pub fn new(underlying_type: UnderlyingType, element_size: i64, element_size_unit: ElementSizeUnit) -> Self {
+        Self {
+            underlying_type,
+            element_size,
+            element_size_unit,
+            with_histogram: false,
+        }
+    }
+    /// Every trade, decoded into `Tick`s.
+    pub fn ticks() -> Self {
+        Self::new(UnderlyingType::Tick, 1, ElementSizeUnit::UnderlyingUnits)
+    }
+    /// Bars of `ticks` trades each.
+    pub fn tick_bars(ticks: i64) -> Self {
+        Self::new(UnderlyingType::Tick, ticks, ElementSizeUnit::UnderlyingUnits)
+    }
+    pub fn minute_bars(minutes: i64) -> Self {
+        Self::new(UnderlyingType::MinuteBar, minutes, ElementSizeUnit::UnderlyingUnits)
+    }
+    pub fn daily_bars(days: i64) -> Self {
+        Self::new(UnderlyingType::DailyBar, days, ElementSizeUnit::UnderlyingUnits)
+    }
+    /// Bars that close after `volume` contracts trade.
+    pub fn volume_bars(volume: i64) -> Self {
+        Self::new(UnderlyingType::Tick, volume, ElementSizeUnit::Volume)
+    }
+    /// Bars that close once their range reaches `ticks` ticks.
+    pub fn range_bars(ticks: i64) -> Self {
+        Self::new(UnderlyingType::Tick, ticks, ElementSizeUnit::Range)
+    }
+    pub fn renko(ticks: i64) -> Self {
+        Self::new(UnderlyingType::Tick, ticks, ElementSizeUnit::Renko)
+    }
+    pub fn with_histogram(mut self, with_histogram: bool) -> Self {
+        self.with_histogram = with_histogram;
+        self
+    }
+    /// True if the chart sends individual ticks rather than bars.
+    pub fn is_tick_stream(&self) -> bool {
+        self == &Self::ticks()
+    }
+}
+
+/// How much history a chart request loads before going live.
+/// The server accepts any combination of the fields.
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeRange {
+    pub closest_timestamp: Option<DateTime<Utc>>,
+    pub closest_tick_id: Option<i64>,
+    pub as_far_as_timestamp: Option<DateTime<Utc>>,
+    pub as_much_as_elements: Option<i64>,
+}
+impl TimeRange {
+    pub fn closest_timestamp(timestamp: DateTime<Utc>) -> Self {
+        Self {
+            closest_timestamp: Some(timestamp),
+            ..Default::default()
+        }
+    }
+    pub fn closest_tick_id(tick_id: i64) -> Self {
+        Self {
+            closest_tick_id: Some(tick_id),
+            ..Default::default()
+        }
+    }
+    pub fn as_far_as_timestamp(timestamp: DateTime<Utc>) -> Self {
+        Self {
+            as_far_as_timestamp: Some(timestamp),
+            ..Default::default()
+        }
+    }
+    pub fn as_much_as_elements(elements: i64) -> Self {
+        Self {
+            as_much_as_elements: Some(elements),
+            ..Default::default()
+        }
+    }
+    pub fn and_as_much_as_elements(mut self, elements: i64) -> Self {
+        self.as_much_as_elements = Some(elements);
+        self
+    }
+    pub fn and_as_far_as_timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.as_far_as_timestamp = Some(timestamp);
+        self
+    }
+}
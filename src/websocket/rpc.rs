@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::SinkExt;
+use log::debug;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::oneshot;
+use tokio_tungstenite::tungstenite::Message;
+
+use super::connection::SharedWriteWs;
+use crate::error::Error;
+
+/// The `{s, i, d}` envelope the server sends back for every request made on a socket.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WsResponse {
+    #[serde(rename = "s")]
+    pub status: i64,
+    #[serde(rename = "i")]
+    pub request_id: usize,
+    #[serde(rename = "d")]
+    #[serde(default)]
+    pub data: Value,
+}
+impl WsResponse {
+    pub fn is_ok(&self) -> bool {
+        self.status == 200
+    }
+    /// The error a response with a status other than 200 is returned as, its `d` field is the message.
+    pub fn into_error(self) -> Error {
+        Error::Api {
+            status: u16::try_from(self.status).unwrap_or_default(),
+            message: self
+                .data
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| self.data.to_string()),
+        }
+    }
+}
+
+
+/// Formats a request frame: endpoint, request id, an empty query line and the body.
+pub fn format_request(endpoint: &str, request_id: usize, body: Option<&Value>) -> String {
+    match body {
+        Some(body) => format!("{}\n{}\n\n{}", endpoint, request_id, body),
+        None => format!("{}\n{}\n\n", endpoint, request_id),
+    }
+}
+
+/// Allocates request ids and keeps the requests that are still waiting for a response.
+#[derive(Debug)]
+pub struct PendingRequests {
+    next_id: AtomicUsize,
+    pending: std::sync::Mutex<HashMap<usize, oneshot::Sender<WsResponse>>>,
+}
+impl Default for PendingRequests {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl PendingRequests {
+    pub fn new() -> Self {
+        Self {
+            next_id: AtomicUsize::new(1),
+            pending: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+    /// An id for a request nobody waits on, such as `authorize`. Its response is dropped as unmatched.
+    pub fn next_request_id(&self) -> usize {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+    pub fn register(&self) -> (usize, oneshot::Receiver<WsResponse>) {
+        let request_id = self.next_request_id();
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock().unwrap().insert(request_id, sender);
+        (request_id, receiver)
+    }
+    /// Hands the response to whoever made the request.
+    /// Returns false if no request with that id is waiting.
+    pub fn resolve(&self, response: WsResponse) -> bool {
+        match self.pending.lock().unwrap().remove(&response.request_id) {
+            Some(sender) => sender.send(response).is_ok(),
+            None => false,
+        }
+    }
+    pub fn forget(&self, request_id: usize) {
+        self.pending.lock().unwrap().remove(&request_id);
+    }
+    /// Fails every waiting request with `Error::ConnectionClosed`.
+    pub fn cancel_all(&self) {
+        self.pending.lock().unwrap().clear();
+    }
+    pub fn len(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Sends requests on a socket and waits for the matching response.
+/// The socket's listener must pass every `{s, i, d}` frame to `PendingRequests::resolve`.
+#[derive(Clone)]
+pub struct WsRpc {
+    writer: SharedWriteWs,
+    pending: Arc<PendingRequests>,
+    timeout: Duration,
+}
+impl WsRpc {
+    pub fn new(writer: SharedWriteWs, pending: Arc<PendingRequests>) -> Self {
+        Self {
+            writer,
+            pending,
+            timeout: Duration::from_secs(30),
+        }
+    }
+    /// How long to wait for a response before giving up. Defaults to 30 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+    pub fn pending(&self) -> &Arc<PendingRequests> {
+        &self.pending
+    }
+    pub fn writer(&self) -> &SharedWriteWs {
+        &self.writer
+    }
+    /// A response with a status other than 200 is returned as `WsResponse::into_error`.
+    pub async fn request(&self, endpoint: &str, body: Option<Value>) -> Result<WsResponse, Error> {
+        let (request_id, receiver) = self.pending.register();
+        let frame = format_request(endpoint, request_id, body.as_ref());
+        debug!("Sending request {}", frame);
+        if let Err(e) = self.writer.lock().await.send(Message::Text(frame)).await {
+            self.pending.forget(request_id);
+            return Err(e.into());
+        }
+        match tokio::time::timeout(self.timeout, receiver).await {
+            Ok(Ok(response)) => {
+                if response.is_ok() {
+                    Ok(response)
+                } else {
+                    Err(response.into_error())
+                }
+            }
+            Ok(Err(_)) => Err(Error::ConnectionClosed),
+            Err(_) => {
+                self.pending.forget(request_id);
+                Err(Error::Timeout)
+            }
+        }
+    }
+    /// Same as `request`, but deserializes the `d` field of the response.
+    pub async fn request_as<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        body: Option<Value>,
+    ) -> Result<T, Error> {
+        let response = self.request(endpoint, body).await?;
+        Ok(serde_json::from_value::<T>(response.data)?)
+    }
+}
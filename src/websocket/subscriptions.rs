@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{watch, Mutex};
+use tokio::task::JoinHandle;
+
+use crate::error::Error;
+use crate::models::time_and_sales::TickTape;
+
+use super::{
+    reconnect::ConnectionState,
+    requests::{MarketData, MarketDataRequest},
+    rpc::WsRpc,
+};
+
+/// The ids returned by `md/getChart`, `historicalId` is the one `md/cancelChart` expects.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct ChartSubscriptionIds {
+    pub historical_id: i64,
+    pub realtime_id: i64,
+}
+
+#[derive(Debug, Clone)]
+struct ActiveSubscription {
+    request: MarketDataRequest,
+    consumers: usize,
+}
+
+#[derive(Default)]
+struct SubscriptionsState {
+    active: HashMap<String, ActiveSubscription>,
+    rpc: Option<WsRpc>,
+}
+
+/// The set of market data requests a connection should be subscribed to.
+/// Duplicate requests from different consumers are reference counted, so the server only sees
+/// one subscription and it is only cancelled when the last consumer unsubscribes.
+/// While a socket is attached every change is sent to the server right away, otherwise it is
+/// sent when the next socket attaches.
+#[derive(Clone, Default)]
+pub struct MarketDataSubscriptions {
+    state: Arc<Mutex<SubscriptionsState>>,
+    tick_tape: Option<Arc<std::sync::Mutex<TickTape>>>,
+}
+impl MarketDataSubscriptions {
+    pub fn new(requests: &[MarketDataRequest]) -> Self {
+        let active = requests
+            .iter()
+            .fold(HashMap::new(), |mut active: HashMap<String, ActiveSubscription>, request| {
+                active
+                    .entry(request.subscription_key())
+                    .and_modify(|subscription| subscription.consumers += 1)
+                    .or_insert(ActiveSubscription {
+                        request: request.clone(),
+                        consumers: 1,
+                    });
+                active
+            });
+        Self {
+            state: Arc::new(Mutex::new(SubscriptionsState { active, rpc: None })),
+            tick_tape: None,
+        }
+    }
+    /// The tape is cleared of a chart once it is cancelled or replaced by a new subscription.
+    pub fn with_tick_tape(mut self, tick_tape: Arc<std::sync::Mutex<TickTape>>) -> Self {
+        self.tick_tape = Some(tick_tape);
+        self
+    }
+    fn clear_ticks(&self, request: &MarketDataRequest) {
+        if let (MarketData::Chart, Some(tick_tape)) = (request.data_type, &self.tick_tape) {
+            tick_tape.lock().unwrap().clear(request.historical_id);
+        }
+    }
+    /// Fails without subscribing if the kind of data can't be subscribed to, see `MarketDataRequest::subscribe_endpoint`.
+    /// The lock is never held while waiting on the server, the request is counted right away and
+    /// dropped again if the server refuses it.
+    pub async fn subscribe(&self, request: MarketDataRequest) -> Result<(), Error> {
+        request.subscribe_endpoint()?;
+        let key = request.subscription_key();
+        let rpc = {
+            let mut state = self.state.lock().await;
+            if let Some(subscription) = state.active.get_mut(&key) {
+                subscription.consumers += 1;
+                return Ok(());
+            }
+            state.active.insert(
+                key.clone(),
+                ActiveSubscription {
+                    request: request.clone(),
+                    consumers: 1,
+                },
+            );
+            state.rpc.clone()
+        };
+        let Some(rpc) = rpc else {
+            return Ok(());
+        };
+        let mut request = request;
+        match send_subscribe(&rpc, &mut request).await {
+            Ok(()) => {
+                self.store_ids(&key, &request).await;
+                Ok(())
+            }
+            Err(e) => {
+                let mut state = self.state.lock().await;
+                if let Some(subscription) = state.active.get_mut(&key) {
+                    subscription.consumers -= 1;
+                    if subscription.consumers == 0 {
+                        state.active.remove(&key);
+                    }
+                }
+                Err(e)
+            }
+        }
+    }
+    /// Drops one consumer of the request, cancelling it on the server if it was the last one.
+    pub async fn unsubscribe(&self, request: &MarketDataRequest) -> Result<(), Error> {
+        let key = request.subscription_key();
+        let (subscription, rpc) = {
+            let mut state = self.state.lock().await;
+            let Some(subscription) = state.active.get_mut(&key) else {
+                return Ok(());
+            };
+            subscription.consumers -= 1;
+            if subscription.consumers > 0 {
+                return Ok(());
+            }
+            let Some(subscription) = state.active.remove(&key) else {
+                return Ok(());
+            };
+            (subscription, state.rpc.clone())
+        };
+        self.clear_ticks(&subscription.request);
+        if let Some(rpc) = rpc {
+            rpc.request(
+                subscription.request.unsubscribe_endpoint()?,
+                Some(subscription.request.unsubscribe_body()),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+    /// Keeps the ids the server assigned to a chart, if it is still subscribed to.
+    async fn store_ids(&self, key: &str, request: &MarketDataRequest) {
+        if request.data_type != MarketData::Chart {
+            return;
+        }
+        if let Some(subscription) = self.state.lock().await.active.get_mut(key) {
+            if subscription.request.historical_id != request.historical_id {
+                self.clear_ticks(&subscription.request);
+            }
+            subscription.request.historical_id = request.historical_id;
+            subscription.request.realtime_id = request.realtime_id;
+        }
+    }
+    /// The requests currently subscribed to, charts carry the ids the server assigned them.
+    pub async fn active(&self) -> Vec<MarketDataRequest> {
+        let state = self.state.lock().await;
+        state.active.values().map(|s| s.request.clone()).collect()
+    }
+    pub async fn consumers(&self, request: &MarketDataRequest) -> usize {
+        let state = self.state.lock().await;
+        state
+            .active
+            .get(&request.subscription_key())
+            .map_or(0, |s| s.consumers)
+    }
+    /// Sends every active request on a freshly opened socket and keeps it for later changes.
+    /// Requests subscribed while this runs are sent by `subscribe` itself.
+    pub async fn attach(&self, rpc: WsRpc) {
+        let keys = {
+            let mut state = self.state.lock().await;
+            state.rpc = Some(rpc.clone());
+            state.active.keys().cloned().collect::<Vec<_>>()
+        };
+        for key in keys {
+            // skip the ones unsubscribed in the meantime
+            let Some(mut request) = self.state.lock().await.active.get(&key).map(|s| s.request.clone()) else {
+                continue;
+            };
+            match send_subscribe(&rpc, &mut request).await {
+                Ok(()) => {
+                    info!("Subscribed to {}", request.summarize());
+                    self.store_ids(&key, &request).await;
+                }
+                Err(e) => error!("Could not subscribe to {} {:?}", request.summarize(), e),
+            }
+        }
+    }
+    pub async fn detach(&self) {
+        self.state.lock().await.rpc = None;
+    }
+}
+
+async fn send_subscribe(rpc: &WsRpc, request: &mut MarketDataRequest) -> Result<(), Error> {
+    let response = rpc
+        .request(request.subscribe_endpoint()?, Some(request.subscribe_body()))
+        .await?;
+    if request.data_type == MarketData::Chart {
+        let ids = serde_json::from_value::<ChartSubscriptionIds>(response.data)?;
+        request.historical_id = ids.historical_id;
+        request.realtime_id = ids.realtime_id;
+    }
+    Ok(())
+}
+
+/// Returned by `TradovateClient::spawn_market_data_socket`, used to change what the
+/// connection is subscribed to and to follow its state.
+pub struct MarketDataHandle {
+    pub subscriptions: MarketDataSubscriptions,
+    pub state: watch::Receiver<ConnectionState>,
+    pub task: JoinHandle<Result<(), Error>>,
+}
+impl MarketDataHandle {
+    pub async fn subscribe(&self, request: MarketDataRequest) -> Result<(), Error> {
+        self.subscriptions.subscribe(request).await
+    }
+    pub async fn unsubscribe(&self, request: &MarketDataRequest) -> Result<(), Error> {
+        self.subscriptions.unsubscribe(request).await
+    }
+    pub fn is_connected(&self) -> bool {
+        self.state.borrow().is_connected()
+    }
+    /// Closes the connection for good.
+    pub fn abort(&self) {
+        self.task.abort();
+    }
+}